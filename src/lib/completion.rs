@@ -0,0 +1,247 @@
+use std::io::{Error, ErrorKind, Result};
+
+use crate::task::Task;
+use crate::task_list;
+
+const BASH: &str = r#"_toduit() {
+    local cur prev cmd kind=""
+    cur="${COMP_WORDS[COMP_CWORD]//\\ / }"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    cmd="${COMP_WORDS[1]}"
+
+    if [ "$COMP_CWORD" -eq 1 ]; then
+        kind="commands"
+    else
+        case "$prev" in
+            -p|--project) kind="projects" ;;
+            -l|--list) kind="lists" ;;
+        esac
+    fi
+
+    if [ -z "$kind" ]; then
+        local args=0 i
+        for (( i=2; i < COMP_CWORD; i++ )); do
+            case "${COMP_WORDS[i]}" in
+                -p|--project|-l|--list|-q|--query|-y|--year|-d|--date|-t|--time|-n|--notice) (( i++ )) ;;
+                -*) ;;
+                *) (( args++ )) ;;
+            esac
+        done
+
+        case "$cmd:$args" in
+            add:0|change-project:0|rename:0) kind="tasks" ;;
+            add:*) kind="tasks,lists" ;;
+            change-project:*) kind="tasks,projects" ;;
+            cancel:*|finish:*|unlist:*|move:*|wait:0|tag:[1-9]*|untag:[1-9]*) kind="tasks" ;;
+            list:0) kind="lists" ;;
+            project:1) kind="projects" ;;
+            lists:1) kind="lists" ;;
+        esac
+    fi
+
+    [ -z "$kind" ] && return
+
+    local IFS=$'\n'
+    COMPREPLY=( $(toduit __complete "$kind" "$cur" 2>/dev/null | sed 's/ /\\ /g') )
+}
+
+complete -F _toduit toduit
+"#;
+
+const ZSH: &str = r#"#compdef toduit
+
+_toduit() {
+    local -a candidates
+    local kind="" cmd="${words[2]}" prev="${words[CURRENT-1]}"
+
+    if (( CURRENT == 2 )); then
+        kind="commands"
+    else
+        case "$prev" in
+            -p|--project) kind="projects" ;;
+            -l|--list) kind="lists" ;;
+        esac
+    fi
+
+    if [[ -z "$kind" ]]; then
+        local args=0 i
+        for (( i = 3; i < CURRENT; i++ )); do
+            case "${words[i]}" in
+                -p|--project|-l|--list|-q|--query|-y|--year|-d|--date|-t|--time|-n|--notice) (( i++ )) ;;
+                -*) ;;
+                *) (( args++ )) ;;
+            esac
+        done
+
+        case "$cmd:$args" in
+            add:0|change-project:0|rename:0) kind="tasks" ;;
+            add:*) kind="tasks,lists" ;;
+            change-project:*) kind="tasks,projects" ;;
+            cancel:*|finish:*|unlist:*|move:*|wait:0|tag:[1-9]*|untag:[1-9]*) kind="tasks" ;;
+            list:0) kind="lists" ;;
+            project:1) kind="projects" ;;
+            lists:1) kind="lists" ;;
+        esac
+    fi
+
+    [[ -z "$kind" ]] && return 1
+
+    candidates=("${(@f)$(toduit __complete "$kind" "$PREFIX" 2>/dev/null)}")
+    compadd -a candidates
+}
+
+compdef _toduit toduit
+"#;
+
+const FISH: &str = r#"function __toduit_complete
+    set -l tokens (commandline -opc)
+    set -l current (commandline -ct)
+    set -l cmd $tokens[2]
+
+    if test (count $tokens) -eq 1
+        toduit __complete commands "$current"
+        return
+    end
+
+    switch $tokens[-1]
+        case -p --project
+            toduit __complete projects "$current"
+            return
+        case -l --list
+            toduit __complete lists "$current"
+            return
+    end
+
+    set -l args 0
+    set -l skip 0
+    if test (count $tokens) -gt 2
+        for token in $tokens[3..-1]
+            if test $skip -eq 1
+                set skip 0
+                continue
+            end
+
+            switch $token
+                case -p --project -l --list -q --query -y --year -d --date -t --time -n --notice
+                    set skip 1
+                case '-*'
+                case '*'
+                    set args (math $args + 1)
+            end
+        end
+    end
+
+    switch "$cmd:$args"
+        case add:0 change-project:0 rename:0
+            toduit __complete tasks "$current"
+        case 'add:*'
+            toduit __complete tasks,lists "$current"
+        case 'change-project:*'
+            toduit __complete tasks,projects "$current"
+        case 'cancel:*' 'finish:*' 'unlist:*' 'move:*' wait:0
+            toduit __complete tasks "$current"
+        case tag:0 untag:0
+        case 'tag:*' 'untag:*'
+            toduit __complete tasks "$current"
+        case list:0
+            toduit __complete lists "$current"
+        case project:1
+            toduit __complete projects "$current"
+        case lists:1
+            toduit __complete lists "$current"
+    end
+end
+
+complete -c toduit -f -a '(__toduit_complete)'
+"#;
+
+pub fn get_script(shell: &str) -> Result<&'static str> {
+    match shell {
+        "bash" => Ok(BASH),
+        "zsh" => Ok(ZSH),
+        "fish" => Ok(FISH),
+        _ => Err(Error::new(ErrorKind::InvalidInput, format!("unsupported shell {}", shell))),
+    }
+}
+
+// Several kinds can be requested at once by separating them with commas
+pub fn get_candidates(kinds: &str, prefix: &str) -> Result<Vec<String>> {
+    let mut candidates: Vec<String> = Vec::new();
+    for kind in kinds.split(',') {
+        candidates.append(&mut get_kind_candidates(kind)?);
+    }
+
+    Ok(filter(&mut candidates, prefix))
+}
+
+fn get_kind_candidates(kind: &str) -> Result<Vec<String>> {
+    let candidates: Vec<String> = match kind {
+        // Finished tasks cannot be listed, moved or finished again
+        "tasks" => Task::get_all(false, "")?
+            .into_iter()
+            .filter(|t| !t.is_finished())
+            .map(|t| t.task_name)
+            .collect(),
+        "projects" => get_projects()?,
+        "lists" => task_list::get_names(),
+        _ => return Err(Error::new(ErrorKind::InvalidInput, format!("unknown completion {}", kind))),
+    };
+
+    Ok(candidates)
+}
+
+pub fn filter(candidates: &mut Vec<String>, prefix: &str) -> Vec<String> {
+    let prefix = prefix.to_lowercase();
+    candidates.retain(|c| c.to_lowercase().starts_with(&prefix));
+    candidates.sort();
+    candidates.dedup();
+    candidates.to_vec()
+}
+
+fn get_projects() -> Result<Vec<String>> {
+    Ok(crate::project::get_names())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+    use crate::task_list::TaskList;
+
+    fn to_strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn filters_by_prefix() {
+        let mut candidates = to_strings(&["today", "Today", "Queued", "Today", "tomorrow", "Someday"]);
+        assert_eq!(filter(&mut candidates.clone(), "to"), to_strings(&["Today", "today", "tomorrow"]));
+        assert_eq!(filter(&mut candidates.clone(), "TOD"), to_strings(&["Today", "today"]));
+        assert_eq!(filter(&mut candidates.clone(), "x"), Vec::<String>::new());
+        assert_eq!(filter(&mut candidates, ""), to_strings(&["Queued", "Someday", "Today", "today", "tomorrow"]));
+    }
+
+    #[test]
+    fn offers_open_tasks() {
+        let root = Root::new();
+        root.add_task("Call Bob", "Home");
+        let listed = root.add_task("Call Carol", "Home");
+        TaskList::get("Today").add(listed).unwrap();
+        root.add_task("Cancel gym", "Home").finish("Task Canceled").unwrap();
+        root.add_task("Write report", "Work");
+
+        assert_eq!(get_candidates("tasks", "c").unwrap(), to_strings(&["Call Bob", "Call Carol"]));
+        assert_eq!(get_candidates("tasks,lists", "").unwrap().len(), 7);
+        assert_eq!(get_candidates("projects,lists", "w").unwrap(), to_strings(&["Waiting", "Work"]));
+        assert_eq!(get_candidates("dates", "").unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn has_scripts_for_supported_shells() {
+        for shell in &["bash", "zsh", "fish"] {
+            assert!(get_script(shell).unwrap().contains("toduit __complete"));
+        }
+
+        assert_eq!(get_script("tcsh").unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,311 @@
+use std::io::{Error, ErrorKind, Result};
+
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use crate::layout;
+use crate::layout::Location;
+use crate::storage;
+use crate::task::Task;
+use crate::task_list;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IssueKind {
+    Unreadable,
+    Misplaced,
+    BrokenListEntry,
+    MissingId,
+    WrongPath,
+    Duplicate,
+    TrailingGarbage,
+}
+
+#[derive(Debug)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub file: String,
+    pub message: String,
+    pub fixable: bool,
+}
+
+impl Issue {
+    fn new(kind: IssueKind, file: &str, message: &str, fixable: bool) -> Issue {
+        Issue {
+            kind,
+            file: file.to_string(),
+            message: message.to_string(),
+            fixable,
+        }
+    }
+}
+
+// Scans every year of the project folder and every configured list
+pub fn check() -> Result<Vec<Issue>> {
+    let mut issues: Vec<Issue> = Vec::new();
+    let root_folder = crate::setting::get_root_folder();
+    let projects_folder = crate::setting::get_project_folder();
+
+    let mut task_files: Vec<String> = WalkDir::new(&projects_folder)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.path().to_str().map(|p| p.to_string()))
+        .filter(|p| p.ends_with(".md"))
+        .collect();
+    task_files.sort();
+
+    for file in &task_files {
+        check_task_file(file, &mut issues)?;
+    }
+
+    check_duplicates(&mut issues)?;
+
+    for list in task_list::get_names() {
+        check_list(&format!("{}/{}", root_folder, list), &mut issues)?;
+    }
+
+    Ok(issues)
+}
+
+pub fn fix(issue: &Issue) -> Result<()> {
+    match issue.kind {
+        IssueKind::BrokenListEntry => task_list::remove_entry(&issue.file),
+        IssueKind::MissingId => rewrite_task(&issue.file, |task, _body| {
+            task.id = Uuid::new_v4().to_string();
+        }),
+        IssueKind::WrongPath => {
+            let expected = get_location(&issue.file)
+                .map(|l| l.with_status(false).get_relative_path())
+                .unwrap_or_default();
+            rewrite_task(&issue.file, |task, _body| task.path = expected.to_string())
+        }
+        IssueKind::TrailingGarbage => rewrite_task(&issue.file, |_task, body| {
+            if let Some(length) = get_garbage_length(body) {
+                body.truncate(body.len() - length);
+            }
+        }),
+        IssueKind::Duplicate => fix_duplicate(&issue.file),
+        IssueKind::Unreadable | IssueKind::Misplaced => Ok(()),
+    }
+}
+
+fn check_task_file(file: &str, issues: &mut Vec<Issue>) -> Result<()> {
+    let contents = match storage::read_to_string(file) {
+        Ok(c) => c,
+        Err(e) => {
+            issues.push(Issue::new(IssueKind::Unreadable, file, &e.to_string(), false));
+            return Ok(());
+        }
+    };
+
+    let task = match Task::parse(&contents) {
+        Ok(t) => t,
+        Err(e) => {
+            issues.push(Issue::new(IssueKind::Unreadable, file, &e.to_string(), false));
+            return Ok(());
+        }
+    };
+
+    if task.id.trim().is_empty() || task.id == "~" {
+        issues.push(Issue::new(IssueKind::MissingId, file, "task has no id", true));
+    }
+
+    match get_location(file) {
+        Some(location) => {
+            let expected = location.with_status(false).get_relative_path();
+            if task.path != expected {
+                let message = format!("path is {} but the file is at {}", task.path, expected);
+                issues.push(Issue::new(IssueKind::WrongPath, file, &message, true));
+            }
+        }
+        None => {
+            let message = format!("file does not match the path template {}", layout::get_template());
+            issues.push(Issue::new(IssueKind::Misplaced, file, &message, false));
+        }
+    }
+
+    let body = contents.splitn(3, "---").nth(2).unwrap_or("");
+    if let Some(length) = get_garbage_length(body) {
+        let message = format!(
+            "{} bytes at the end repeat the text before them: {:?}",
+            length,
+            &body[body.len() - length..]
+        );
+        issues.push(Issue::new(IssueKind::TrailingGarbage, file, &message, true));
+    }
+
+    Ok(())
+}
+
+// A task should only ever be in one of its new and done locations
+fn check_duplicates(issues: &mut Vec<Issue>) -> Result<()> {
+    for (file, location) in layout::get_task_files().iter().filter(|(_f, l)| l.is_new) {
+        let done_file = location.with_status(false).get_file_path();
+        if !storage::exists(&done_file) {
+            continue;
+        }
+
+        let identical = storage::read_to_string(file)? == storage::read_to_string(&done_file)?;
+        let message = if identical {
+            format!("same task is also at {}", done_file)
+        } else {
+            format!("a different copy of the task is at {}, compare them and remove one", done_file)
+        };
+
+        issues.push(Issue::new(IssueKind::Duplicate, file, &message, identical));
+    }
+
+    Ok(())
+}
+
+fn check_list(list_folder: &str, issues: &mut Vec<Issue>) -> Result<()> {
+    for entry in WalkDir::new(list_folder)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok()) {
+            let file = match entry.path().to_str() {
+                Some(f) if f.ends_with(".md") => f,
+                _ => continue,
+            };
+
+            let tasks = task_list::get_entry_tasks(file)?;
+            if tasks.is_empty() {
+                issues.push(Issue::new(IssueKind::BrokenListEntry, file, "entry has no task link", true));
+                continue;
+            }
+
+            for (task_name, task) in tasks {
+                if task.is_err() {
+                    let message = format!("entry points at missing task {}", task_name);
+                    issues.push(Issue::new(IssueKind::BrokenListEntry, file, &message, true));
+                }
+            }
+    }
+
+    Ok(())
+}
+
+fn rewrite_task<F: FnOnce(&mut Task, &mut String)>(file: &str, change: F) -> Result<()> {
+    let contents = storage::read_to_string(file)?;
+    let mut task = Task::parse(&contents)?;
+    let mut body = contents.splitn(3, "---").nth(2).unwrap_or("").to_string();
+
+    change(&mut task, &mut body);
+
+    let ymltask = serde_yaml::to_string(&task).unwrap();
+    storage::write(file, &format!("{} \n---{}", ymltask, body))
+}
+
+// Identical copies are resolved to match the lists, a listed task
+// belongs in the project folder and anything else stays new
+fn fix_duplicate(file: &str) -> Result<()> {
+    let done_file = get_location(file)
+        .map(|l| l.with_status(false).get_file_path())
+        .unwrap_or_default();
+    if storage::read_to_string(file)? != storage::read_to_string(&done_file)? {
+        return Err(Error::new(ErrorKind::InvalidData, "the copies are different"));
+    }
+
+    let task = Task::parse(&storage::read_to_string(file)?)?;
+    if crate::task_list::get_lists_for_task(&task.task_name).is_empty() {
+        storage::remove_file(&done_file)
+    } else {
+        storage::remove_file(file)
+    }
+}
+
+// Saves that shrank the frontmatter used to leave the old end of the file
+// behind, which shows up as a tail that repeats the text just before it.
+// The body ends in a finished comment, or the link a task was created
+// with, so the tail has to follow one of those and not be a comment
+// itself. Repeats inside a comment, like a checklist item written twice,
+// are left alone
+fn get_garbage_length(body: &str) -> Option<usize> {
+    let length = body.len();
+
+    (3..=length / 2).rev().find(|&k| {
+        let start = length - k;
+        if !body.is_char_boundary(start) || !body.is_char_boundary(start - k) {
+            return false;
+        }
+
+        let before = &body[..start];
+        let tail = &body[start..];
+        !tail.trim().is_empty()
+            && !tail.starts_with("#####")
+            && before.contains("##### ")
+            && (before.ends_with("\n\n") || is_link_line(before.lines().last().unwrap_or("")))
+            && &body[start - k..start] == tail
+    })
+}
+
+fn is_link_line(line: &str) -> bool {
+    line.starts_with("[link](") && line.ends_with(')')
+}
+
+fn get_location(file: &str) -> Option<Location> {
+    let prefix = format!("{}/", crate::setting::get_project_folder());
+    Location::parse(file.strip_prefix(&prefix)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+    use crate::task_list::TaskList;
+
+    const BODY: &str = "\n##### 10/19/2026 09:00 AM \nAdded to list Today\n\n##### 10/18/2026 08:00 AM \nTask Created\n\n";
+
+    #[test]
+    fn finds_old_end_after_last_comment() {
+        let body = format!("{}ted\n\n", BODY);
+        assert_eq!(get_garbage_length(&body), Some(5));
+    }
+
+    #[test]
+    fn finds_old_end_after_link() {
+        let body = "\n##### 10/18/2026 08:00 AM \nTask Created\n\n\n\n[link](https://example.com)ple.com)";
+        assert_eq!(get_garbage_length(body), Some(8));
+    }
+
+    #[test]
+    fn keeps_repeated_checklist_items() {
+        let body = format!("{}- [ ] a\n- [ ] a\n", BODY.trim_end_matches('\n'));
+        assert_eq!(get_garbage_length(&body), None);
+    }
+
+    #[test]
+    fn keeps_repeated_words() {
+        assert_eq!(get_garbage_length(&format!("{}hahaha", BODY)), None);
+        assert_eq!(get_garbage_length("\n##### 10/18/2026 08:00 AM \nhahaha"), None);
+    }
+
+    #[test]
+    fn keeps_comments_logged_twice() {
+        let comment = "##### 10/19/2026 09:00 AM \nAdded to list Today\n\n";
+        assert_eq!(get_garbage_length(&format!("\n{}{}", comment, comment)), None);
+    }
+
+    #[test]
+    fn removes_broken_entries_from_the_order() {
+        let root = Root::new();
+        let today = TaskList::get("Today");
+        for task_name in &["One", "Two", "Three"] {
+            today.add(root.add_task(task_name, "Home")).unwrap();
+        }
+
+        std::fs::remove_file(format!("{}/Projects/2026/Home/Two.md", root.path)).unwrap();
+        std::fs::write(format!("{}/Today/Empty.md", root.path), "no link here\n").unwrap();
+
+        let issues: Vec<Issue> = check().unwrap().into_iter().filter(|i| i.kind == IssueKind::BrokenListEntry).collect();
+        assert_eq!(issues.len(), 2, "{:?}", issues);
+        for issue in &issues {
+            fix(issue).unwrap();
+        }
+
+        assert!(!root.exists("Today/Two.md"));
+        assert!(!root.exists("Today/Empty.md"));
+        assert_eq!(std::fs::read_to_string(format!("{}/Today/.order", root.path)).unwrap(), "One.md\nThree.md\n");
+        assert!(check().unwrap().is_empty());
+    }
+}
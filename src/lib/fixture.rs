@@ -0,0 +1,72 @@
+use std::env;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::task::Task;
+
+pub const LISTS: &str = "Today,Queued,Waiting,Someday";
+
+static LOCK: Mutex<()> = Mutex::new(());
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// Tests that use the task store share the settings in the environment, so
+// they run one at a time, each in a new root folder that is removed after
+pub struct Root {
+    pub path: String,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Root {
+    pub fn new() -> Root {
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = env::temp_dir()
+            .join(format!("toduit-test-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::SeqCst)))
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        for var in &["TODUIT_DRY_RUN", "TODUIT_LIST_LIMITS", "TODUIT_YEAR", "TODUIT_PATH_TEMPLATE", "TODUIT_API_TOKEN"] {
+            env::remove_var(var);
+        }
+
+        env::set_var("TODUIT_ROOT_FOLDER", &path);
+        env::set_var("TODUIT_PROJECT_FOLDER_NAME", "Projects");
+        env::set_var("TODUIT_PROJECT_FOLDER", format!("{}/Projects", path));
+        env::set_var("TODUIT_JOURNAL_FOLDER_NAME", "Journal");
+        env::set_var("TODUIT_JOURNAL_FOLDER", format!("{}/Journal/2026/10 - October", path));
+        env::set_var("TODUIT_REVIEW_FOLDER_NAME", "Review");
+        env::set_var("TODUIT_REVIEW_FOLDER", format!("{}/Review/2026/10 - October", path));
+        env::set_var("TODUIT_ARCHIVE_FOLDER_NAME", "Archive");
+        env::set_var("TODUIT_REMINDER_FILE", format!("{}/reminders.rem", path));
+        env::set_var("TODUIT_TODO_LISTS", LISTS);
+
+        for folder in ["Projects", "Journal/2026/10 - October", "Review/2026/10 - October"]
+            .iter()
+            .map(|f| f.to_string())
+            .chain(LISTS.split(',').map(|l| l.to_string())) {
+                fs::create_dir_all(format!("{}/{}", path, folder)).unwrap();
+        }
+
+        fs::write(format!("{}/reminders.rem", path), "").unwrap();
+
+        Root { path, _lock: lock }
+    }
+
+    pub fn add_task(&self, task_name: &str, project: &str) -> Task {
+        let task = Task::new(task_name, project, &2026);
+        task.add("").unwrap();
+        task
+    }
+
+    pub fn exists(&self, relative: &str) -> bool {
+        std::path::Path::new(&format!("{}/{}", self.path, relative)).exists()
+    }
+}
+
+impl Drop for Root {
+    fn drop(&mut self) {
+        env::remove_var("TODUIT_DRY_RUN");
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
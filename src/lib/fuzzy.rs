@@ -0,0 +1,93 @@
+// Only a match scoring at least this, a whole name ignoring case, is safe
+// to act on without asking
+pub const CLEAR_MATCH: i64 = 1000;
+
+// Scores how well a query matches a candidate, higher is better. Exact
+// matches rank above prefixes, then substrings, then characters that
+// appear in order with the fewest gaps between them
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    let query = query.to_lowercase();
+    let candidate = candidate.to_lowercase();
+
+    if query.is_empty() {
+        return None;
+    }
+
+    let extra = (candidate.len() as i64 - query.len() as i64).abs();
+    if candidate == query {
+        return Some(CLEAR_MATCH);
+    }
+
+    if candidate.starts_with(&query) {
+        return Some((800 - extra).max(601));
+    }
+
+    if let Some(pos) = candidate.find(&query) {
+        return Some((600 - pos as i64 - extra).max(401));
+    }
+
+    let mut score: i64 = 400;
+    let mut last: Option<usize> = None;
+    let mut chars = candidate.char_indices();
+
+    for q in query.chars() {
+        loop {
+            match chars.next() {
+                Some((i, c)) if c == q => {
+                    if let Some(l) = last {
+                        score -= (i - l - 1) as i64;
+                    }
+
+                    last = Some(i);
+                    break;
+                }
+                Some(_) => continue,
+                None => return None,
+            }
+        }
+    }
+
+    Some(score.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_exact_match_as_clear() {
+        assert_eq!(score("write report", "Write Report"), Some(CLEAR_MATCH));
+    }
+
+    #[test]
+    fn ranks_prefix_above_substring_above_subsequence() {
+        let prefix = score("write", "Write report").unwrap();
+        let substring = score("report", "Write report").unwrap();
+        let subsequence = score("wrt", "Write report").unwrap();
+
+        assert!(prefix < CLEAR_MATCH);
+        assert!(prefix > substring);
+        assert!(substring > subsequence);
+    }
+
+    #[test]
+    fn prefers_fewer_gaps() {
+        assert!(score("wr", "Write report") > score("wt", "Write report"));
+    }
+
+    #[test]
+    fn rejects_characters_out_of_order() {
+        assert_eq!(score("tw", "Write"), None);
+        assert_eq!(score("writes", "Write"), None);
+    }
+
+    #[test]
+    fn rejects_empty_query() {
+        assert_eq!(score("", "Write report"), None);
+    }
+
+    #[test]
+    fn keeps_weak_matches_positive() {
+        assert_eq!(score("ab", &format!("a{}b", "x".repeat(500))), Some(1));
+    }
+}
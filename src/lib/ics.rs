@@ -0,0 +1,470 @@
+extern crate chrono;
+
+use chrono::prelude::*;
+use std::io::Result;
+
+use crate::project;
+use crate::task::Task;
+
+const PRODID: &str = "-//toduit//toduit//EN";
+
+#[derive(Debug, Default)]
+pub struct Todo {
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub status: String,
+    pub categories: Vec<String>,
+    pub due_date: Option<NaiveDate>,
+    pub due_time: Option<NaiveTime>,
+}
+
+impl Todo {
+    pub fn is_completed(&self) -> bool {
+        self.status == "COMPLETED" || self.status == "CANCELLED"
+    }
+}
+
+pub fn export(tasks: &[Task]) -> String {
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for task in tasks {
+        lines.append(&mut get_todo(task));
+
+        if task.remind.is_some() {
+            lines.append(&mut get_event(task));
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter()
+        .map(|l| fold_line(l))
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+pub fn parse(contents: &str) -> Vec<Todo> {
+    let mut todos: Vec<Todo> = Vec::new();
+    let mut current: Option<Todo> = None;
+    let mut depth = 0;
+
+    for line in unfold_lines(contents) {
+        let (name, params, value) = match split_property(&line) {
+            Some(p) => p,
+            None => continue,
+        };
+
+        match name.as_str() {
+            "BEGIN" if value == "VTODO" => {
+                current = Some(Todo::default());
+                depth = 0;
+            }
+            "BEGIN" => depth += 1,
+            "END" if value == "VTODO" => {
+                if let Some(todo) = current.take() {
+                    todos.push(todo);
+                }
+            }
+            "END" => depth -= 1,
+            _ => {
+                // Properties of nested components such as VALARM are skipped
+                let todo = match current.as_mut() {
+                    Some(t) if depth == 0 => t,
+                    _ => continue,
+                };
+
+                match name.as_str() {
+                    "UID" => todo.uid = value,
+                    "SUMMARY" => todo.summary = unescape_text(&value),
+                    "DESCRIPTION" => todo.description = unescape_text(&value),
+                    "STATUS" => todo.status = value.to_uppercase(),
+                    "CATEGORIES" => todo.categories = value
+                        .split(',')
+                        .map(|c| unescape_text(c.trim()))
+                        .filter(|c| !c.is_empty())
+                        .collect(),
+                    "DUE" => {
+                        let (date, time) = parse_date(&value, &params);
+                        todo.due_date = date;
+                        todo.due_time = time;
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    todos
+}
+
+pub fn import(contents: &str, project: &str) -> Result<Vec<Task>> {
+    let mut imported: Vec<Task> = Vec::new();
+    let year = crate::setting::get_year().unwrap_or_else(|| Local::now().year());
+
+    for todo in parse(contents) {
+        if todo.summary.is_empty() {
+            continue;
+        }
+
+        if !todo.uid.is_empty() && Task::get_by_id_or_name(&todo.uid, false, "").is_ok() {
+            continue;
+        }
+
+        let task_project = project::get_import_name(project, todo.categories.first().map(|c| c.as_str()))?;
+
+        if Task::get_by_id_or_name(&todo.summary, false, &task_project).is_ok() {
+            eprintln!("skipping {}, a task with that name already exists", todo.summary);
+            continue;
+        }
+
+        let mut task = Task::new(&todo.summary, &task_project, &year);
+        if !todo.uid.is_empty() {
+            task.id = todo.uid.to_string();
+        }
+
+        task.add("")?;
+
+        if !todo.description.is_empty() {
+            task.add_comment(&todo.description, true)?;
+        }
+
+        if todo.is_completed() {
+            task.finish("Task Completed")?;
+        } else if let Some(date) = todo.due_date {
+            let time = match todo.due_time {
+                Some(t) => t.format("%H:%M").to_string(),
+                None => "".to_string(),
+            };
+
+            task.set_reminder(
+                &date.format("%m").to_string(),
+                &date.format("%d").to_string(),
+                &date.format("%Y").to_string(),
+                &time,
+                &0
+            )?;
+        }
+
+        imported.push(task);
+    }
+
+    Ok(imported)
+}
+
+fn get_todo(task: &Task) -> Vec<String> {
+    let finished = match task.is_finished() {
+        true => Some(task.get_finish().map(|(d, _c)| d).unwrap_or(task.updated)),
+        false => None,
+    };
+
+    get_todo_on(task, finished, Local::now().date_naive())
+}
+
+fn get_todo_on(task: &Task, finished: Option<DateTime<Local>>, today: NaiveDate) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}", task.id),
+        format!("DTSTAMP:{}", format_utc(&task.updated)),
+        format!("CREATED:{}", format_utc(&task.created)),
+        format!("LAST-MODIFIED:{}", format_utc(&task.updated)),
+        format!("SUMMARY:{}", escape_text(&task.task_name)),
+        format!("CATEGORIES:{}", escape_text(&task.project)),
+        format!("STATUS:{}", if finished.is_some() { "COMPLETED" } else { "NEEDS-ACTION" }),
+    ];
+
+    if let Some(f) = finished {
+        lines.push(format!("COMPLETED:{}", format_utc(&f)));
+    }
+
+    if let Some(remind) = &task.remind {
+        if let Some(date) = remind.get_next_date(today) {
+            lines.push(format_date("DUE", date, remind.get_time()));
+        }
+
+        lines.append(&mut get_alarm(task));
+    }
+
+    lines.push("END:VTODO".to_string());
+    lines
+}
+
+fn get_event(task: &Task) -> Vec<String> {
+    let remind = match &task.remind {
+        Some(r) => r,
+        None => return Vec::new(),
+    };
+
+    let date = match remind.get_next_date(Local::now().date_naive()) {
+        Some(d) => d,
+        None => return Vec::new(),
+    };
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}-remind", task.id),
+        format!("DTSTAMP:{}", format_utc(&task.updated)),
+        format_date("DTSTART", date, remind.get_time()),
+        format!("SUMMARY:{}", escape_text(&task.task_name)),
+        format!("CATEGORIES:{}", escape_text(&task.project)),
+    ];
+
+    if remind.get_date().is_none() {
+        if remind.month.is_empty() && remind.day.is_empty() {
+            lines.push("RRULE:FREQ=DAILY".to_string());
+        } else if remind.month.is_empty() {
+            lines.push(format!("RRULE:FREQ=MONTHLY;BYMONTHDAY={}", date.day()));
+        } else {
+            lines.push("RRULE:FREQ=YEARLY".to_string());
+        }
+    }
+
+    lines.append(&mut get_alarm(task));
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+fn get_alarm(task: &Task) -> Vec<String> {
+    let remind = match &task.remind {
+        Some(r) => r,
+        None => return Vec::new(),
+    };
+
+    let trigger = if remind.notice > 0 {
+        format!("-P{}D", remind.notice)
+    } else if remind.get_time().is_some() {
+        "PT0S".to_string()
+    } else {
+        return Vec::new();
+    };
+
+    vec![
+        "BEGIN:VALARM".to_string(),
+        "ACTION:DISPLAY".to_string(),
+        format!("DESCRIPTION:{}", escape_text(&task.task_name)),
+        format!("TRIGGER:{}", trigger),
+        "END:VALARM".to_string(),
+    ]
+}
+
+fn format_utc(date: &DateTime<Local>) -> String {
+    date.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_date(name: &str, date: NaiveDate, time: Option<NaiveTime>) -> String {
+    match time {
+        Some(t) => format!("{}:{}", name, date.and_time(t).format("%Y%m%dT%H%M%S")),
+        None => format!("{};VALUE=DATE:{}", name, date.format("%Y%m%d")),
+    }
+}
+
+fn parse_date(value: &str, params: &str) -> (Option<NaiveDate>, Option<NaiveTime>) {
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return (NaiveDate::parse_from_str(value, "%Y%m%d").ok(), None);
+    }
+
+    match NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S") {
+        Ok(dt) if value.ends_with('Z') => {
+            let local = Utc.from_utc_datetime(&dt).with_timezone(&Local);
+            (Some(local.date_naive()), Some(local.time()))
+        }
+        Ok(dt) => (Some(dt.date()), Some(dt.time())),
+        Err(_e) => (NaiveDate::parse_from_str(value, "%Y%m%d").ok(), None),
+    }
+}
+
+// Joins folded continuation lines back onto the line they belong to
+fn unfold_lines(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+
+        lines.push(line.to_string());
+    }
+
+    lines
+}
+
+// Splits a content line into its upper cased name, parameters and value
+fn split_property(line: &str) -> Option<(String, String, String)> {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                let (head, value) = (&line[..i], &line[i + 1..]);
+                let (name, params) = match head.find(';') {
+                    Some(p) => (&head[..p], &head[p + 1..]),
+                    None => (head, ""),
+                };
+
+                return Some((name.to_uppercase(), params.to_uppercase(), value.to_string()));
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(e) => unescaped.push(e),
+            None => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Content lines longer than 75 octets are folded onto continuation
+// lines that start with a single space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reminder::Reminder;
+
+    fn get_task(remind: Option<Reminder>) -> Task {
+        std::env::set_var("TODUIT_PROJECT_FOLDER_NAME", "Projects");
+        let mut task = Task::new("Pay rent, water; power", "Home", &2026);
+        task.remind = remind;
+        task
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+    }
+
+    #[test]
+    fn writes_open_todo() {
+        let task = get_task(None);
+        let lines = get_todo_on(&task, None, today());
+
+        assert_eq!(lines.first().unwrap(), "BEGIN:VTODO");
+        assert_eq!(lines.last().unwrap(), "END:VTODO");
+        assert!(lines.contains(&format!("UID:{}", task.id)));
+        assert!(lines.contains(&"SUMMARY:Pay rent\\, water\\; power".to_string()));
+        assert!(lines.contains(&"STATUS:NEEDS-ACTION".to_string()));
+        assert!(!lines.iter().any(|l| l.starts_with("COMPLETED") || l.starts_with("DUE")));
+        assert!(!lines.contains(&"BEGIN:VALARM".to_string()));
+    }
+
+    #[test]
+    fn writes_finish_date() {
+        let task = get_task(None);
+        let finished = Utc.with_ymd_and_hms(2026, 10, 5, 9, 30, 0).unwrap().with_timezone(&Local);
+        let lines = get_todo_on(&task, Some(finished), today());
+
+        assert!(lines.contains(&"STATUS:COMPLETED".to_string()));
+        assert!(lines.contains(&"COMPLETED:20261005T093000Z".to_string()));
+    }
+
+    #[test]
+    fn writes_due_date_and_alarm() {
+        let task = get_task(Some(Reminder::new("11", "02", "2026", "", &2)));
+        let lines = get_todo_on(&task, None, today());
+
+        let start = lines.iter().position(|l| l == "BEGIN:VALARM").unwrap();
+        assert!(lines.contains(&"DUE;VALUE=DATE:20261102".to_string()));
+        assert_eq!(lines[start..], [
+            "BEGIN:VALARM",
+            "ACTION:DISPLAY",
+            "DESCRIPTION:Pay rent\\, water\\; power",
+            "TRIGGER:-P2D",
+            "END:VALARM",
+            "END:VTODO",
+        ]);
+    }
+
+    #[test]
+    fn alarm_is_at_the_due_time() {
+        let task = get_task(Some(Reminder::new("", "25", "", "14:30", &0)));
+        let lines = get_todo_on(&task, None, today());
+
+        assert!(lines.contains(&"DUE:20261025T143000".to_string()));
+        assert!(lines.contains(&"TRIGGER:PT0S".to_string()));
+    }
+
+    #[test]
+    fn no_alarm_without_time_or_notice() {
+        let task = get_task(Some(Reminder::new("11", "02", "2026", "", &0)));
+        assert!(get_alarm(&task).is_empty());
+        assert!(get_alarm(&get_task(None)).is_empty());
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let line = format!("SUMMARY:{}", "é".repeat(60));
+        let folded = fold_line(&line);
+
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(unfold_lines(&folded).concat(), line);
+    }
+
+    #[test]
+    fn parses_exported_todo() {
+        let task = get_task(Some(Reminder::new("11", "02", "2026", "14:30", &1)));
+        let calendar = get_todo_on(&task, None, today())
+            .iter()
+            .map(|l| fold_line(l))
+            .collect::<String>();
+
+        let todos = parse(&calendar);
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].uid, task.id);
+        assert_eq!(todos[0].summary, task.task_name);
+        assert_eq!(todos[0].categories, vec!["Home"]);
+        assert_eq!(todos[0].description, "");
+        assert_eq!(todos[0].due_date, NaiveDate::from_ymd_opt(2026, 11, 2));
+        assert_eq!(todos[0].due_time, NaiveTime::from_hms_opt(14, 30, 0));
+        assert!(!todos[0].is_completed());
+    }
+}
@@ -2,13 +2,11 @@ extern crate chrono;
 
 use chrono::prelude::*;
 use chrono::DateTime;
-use std::fs::File;
-use std::fs::OpenOptions;
 use std::io::Result;
-use std::io::Write;
-use std::path::Path;
 
+use crate::storage;
 use crate::task::Task;
+use crate::util::link;
 
 pub struct Journal {
     title: String,
@@ -40,81 +38,57 @@ impl Journal {
     }
 
     pub fn create(&self) -> Result<bool> {
-        if Path::new(&self.journal_path).exists() {
+        if storage::exists(&self.journal_path) {
             return Ok(false);
         };
-        
-        let mut journalfile = match File::create(&self.journal_path) {
-            Ok(f) => f,
-            Err(e) => return Err(e)
-        };
 
-        journalfile.write_all(
-            format!(
-                "# {:02}/{:02}/{} {} \n\n",
-                self.created.month(),
-                self.created.day(),
-                self.created.year(),
-                self.title
-            )
-            .as_bytes(),
-        )?;
-        journalfile.write_all(format!("## {}\n\n", self.subheader).as_bytes())?;
+        let contents = format!(
+            "# {:02}/{:02}/{} {} \n\n## {}\n\n",
+            self.created.month(),
+            self.created.day(),
+            self.created.year(),
+            self.title,
+            self.subheader
+        );
 
-        journalfile.sync_data()?;
+        storage::write(&self.journal_path, &contents)?;
 
         Ok(true)
     }
 
+    pub fn has_task_list(&self) -> bool {
+        storage::read_to_string(&self.journal_path)
+            .map(|c| c.lines().any(|l| l.trim() == "## Tasks"))
+            .unwrap_or(false)
+    }
+
     pub fn add_link_to_journal(&self, title: &str, link: &str) -> Result<()> {
         self.create().expect("could not create journal");
-        let mut journalfile = OpenOptions::new()
-            .append(true)
-            .open(&self.journal_path)
-            .expect("could not append to journal");
-
-        journalfile
-                .write_all(format!("* [{}]({})\n", title, link).as_bytes())
-                .expect("could not write to journal");
+        storage::append(&self.journal_path, &format!("* [{}]({})\n", title, link))
+            .expect("could not write to journal");
 
         Ok(())
     }
 
     pub fn add_task_to_journal(&self, task: &Task) {
         self.create().expect("could not create journal");
-        let mut journalfile = OpenOptions::new()
-            .append(true)
-            .open(&self.journal_path)
-            .expect("could not append to journal");
-
         if !&task.is_excluded() {
-            let link = format!("../../../{}", task.path);
-            journalfile
-                .write_all(format!("* [{}]({})\n", task.task_name, link).as_bytes())
+            let link = format!("../../../{}", link::encode(&task.path));
+            storage::append(&self.journal_path, &format!("* [{}]({})\n", link::escape_text(&task.task_name), link))
                 .expect("could not write to journal");
         }
     }
 
     pub fn add_tasks_to_journal(&self, tasks: Vec<Task>) {
-        let mut journalfile = OpenOptions::new()
-            .append(true)
-            .open(&self.journal_path)
-            .expect("could not open journal");
-
-        journalfile
-            .write_all(String::from("## Tasks \n").as_bytes())
-            .expect("could not write to journal");
-
+        let mut contents = String::from("## Tasks \n");
         for task in tasks {
             if !task.is_excluded() {
-                let link = format!("../../../{}", task.path);
-                journalfile
-                    .write_all(format!("* [{}]({})\n", task.task_name, link).as_bytes())
-                    .expect("could not write to journal");
+                let link = format!("../../../{}", link::encode(&task.path));
+                contents.push_str(&format!("* [{}]({})\n", link::escape_text(&task.task_name), link));
             }
         }
 
-        journalfile.sync_data().expect("could not sync data");
+        storage::append(&self.journal_path, &contents).expect("could not write to journal");
     }
 }
 
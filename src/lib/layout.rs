@@ -0,0 +1,369 @@
+use std::io::{Error, ErrorKind, Result};
+
+use chrono::prelude::*;
+use walkdir::WalkDir;
+
+use crate::storage;
+
+pub const DEFAULT_TEMPLATE: &str = "{year}/{project}/{status}/{name}.md";
+const NEW_STATUS: &str = "new";
+const EMPTY_FILE_NAME: &str = "task";
+const MAX_FILE_NAME_LENGTH: usize = 200;
+
+// Where a task file sits below the project folder. Templates are made of
+// folder segments that are either literal names or one of `{year}`,
+// `{project}` (which may span several folders) and `{status}` (`new` for
+// new tasks, left out otherwise), ending in `{name}.md` or `{slug}.md`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub year: Option<i32>,
+    pub project: String,
+    pub stem: String,
+    pub is_new: bool,
+}
+
+impl Location {
+    pub fn new(task_name: &str, project: &str, year: i32, is_new: bool) -> Location {
+        Location {
+            year: Some(year),
+            project: project.to_string(),
+            stem: get_stem(&get_template(), task_name),
+            is_new,
+        }
+    }
+
+    // Parses a path relative to the project folder
+    pub fn parse(relative: &str) -> Option<Location> {
+        Location::parse_with(&get_template(), relative)
+    }
+
+    pub fn parse_with(template: &str, relative: &str) -> Option<Location> {
+        let template: Vec<&str> = template.split('/').collect();
+        let path: Vec<&str> = relative.split('/').filter(|s| !s.is_empty()).collect();
+        let (file_name, folders) = path.split_last()?;
+
+        let mut location = Location {
+            year: None,
+            project: String::new(),
+            stem: file_name.strip_suffix(".md")?.to_string(),
+            is_new: false,
+        };
+
+        match match_segments(&template[..template.len() - 1], folders, &mut location) {
+            true => Some(location),
+            false => None,
+        }
+    }
+
+    // Parses the `path` stored in a task, which is relative to the root
+    pub fn from_task_path(task_path: &str) -> Option<Location> {
+        let prefix = format!("{}/", crate::setting::get_project_folder_name());
+        Location::parse(task_path.strip_prefix(&prefix)?)
+    }
+
+    pub fn format(&self) -> String {
+        self.format_with(&get_template())
+    }
+
+    pub fn format_with(&self, template: &str) -> String {
+        let year = self.year.unwrap_or_else(|| Local::now().year());
+
+        template
+            .split('/')
+            .filter_map(|segment| match segment {
+                "{year}" => Some(year.to_string()),
+                "{project}" => Some(self.project.to_string()),
+                "{status}" if self.is_new => Some(NEW_STATUS.to_string()),
+                "{status}" => None,
+                "{name}.md" | "{slug}.md" => Some(format!("{}.md", self.stem)),
+                literal => Some(literal.to_string()),
+            })
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    pub fn with_status(&self, is_new: bool) -> Location {
+        Location { is_new, ..self.clone() }
+    }
+
+    pub fn with_name(&self, task_name: &str) -> Location {
+        Location { stem: get_stem(&get_template(), task_name), ..self.clone() }
+    }
+
+    // The path relative to the root, which is what tasks and links store
+    pub fn get_relative_path(&self) -> String {
+        format!("{}/{}", crate::setting::get_project_folder_name(), self.format())
+    }
+
+    pub fn get_file_path(&self) -> String {
+        format!("{}/{}", crate::setting::get_project_folder(), self.format())
+    }
+}
+
+pub fn get_template() -> String {
+    crate::setting::get_path_template()
+}
+
+pub fn has_year(template: &str) -> bool {
+    template.split('/').any(|s| s == "{year}")
+}
+
+pub fn validate(template: &str) -> Result<()> {
+    let segments: Vec<&str> = template.split('/').collect();
+    let invalid = |message: &str| Err(Error::new(
+        ErrorKind::InvalidInput,
+        format!("invalid path-template {}: {}", template, message)
+    ));
+
+    match segments.last() {
+        Some(&"{name}.md") | Some(&"{slug}.md") => (),
+        _ => return invalid("it must end with {name}.md or {slug}.md"),
+    }
+
+    for placeholder in &["{project}", "{status}"] {
+        if segments.iter().filter(|s| s == &placeholder).count() != 1 {
+            return invalid(&format!("it must contain {} once", placeholder));
+        }
+    }
+
+    if segments.iter().filter(|s| **s == "{year}").count() > 1 {
+        return invalid("it can only contain {year} once");
+    }
+
+    for segment in &segments[..segments.len() - 1] {
+        let is_placeholder = ["{year}", "{project}", "{status}"].contains(segment);
+        if !is_placeholder && (segment.is_empty() || segment.contains('{') || segment.contains('}')) {
+            return invalid(&format!("{:?} is not a folder name or placeholder", segment));
+        }
+
+        if [".", "..", NEW_STATUS].contains(segment) {
+            return invalid(&format!("{} can not be used as a folder name", segment));
+        }
+    }
+
+    Ok(())
+}
+
+// Returns every task file below the project folder with its location,
+// skipping files the template does not describe
+pub fn get_task_files() -> Vec<(String, Location)> {
+    get_task_files_with(&get_template())
+}
+
+pub fn get_task_files_with(template: &str) -> Vec<(String, Location)> {
+    let project_folder = crate::setting::get_project_folder();
+    let prefix = format!("{}/", project_folder);
+
+    let mut files: Vec<(String, Location)> = WalkDir::new(&project_folder)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.path().to_str().map(|p| p.to_string()))
+        .filter(|p| p.ends_with(".md") && storage::exists(p))
+        .filter_map(|p| {
+            let location = Location::parse_with(template, p.strip_prefix(&prefix)?)?;
+            Some((p, location))
+        })
+        .collect();
+
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+pub fn slugify(value: &str) -> String {
+    let mut slug = String::new();
+    for c in value.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    limit_file_name(slug.trim_end_matches('-'))
+}
+
+// Keeps the task name readable as a file name, only replacing what would
+// create folders or is not allowed on other systems the tasks sync to
+pub fn get_safe_file_name(task_name: &str) -> String {
+    let name: String = task_name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+
+    limit_file_name(name.trim_matches(|c: char| c == '.' || c.is_whitespace()))
+}
+
+fn limit_file_name(name: &str) -> String {
+    let mut end = name.len().min(MAX_FILE_NAME_LENGTH);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    match name[..end].trim_end() {
+        "" => EMPTY_FILE_NAME.to_string(),
+        n => n.to_string(),
+    }
+}
+
+fn get_stem(template: &str, task_name: &str) -> String {
+    match template.ends_with("{slug}.md") {
+        true => slugify(task_name),
+        false => get_safe_file_name(task_name),
+    }
+}
+
+fn match_segments(template: &[&str], path: &[&str], location: &mut Location) -> bool {
+    let (segment, rest) = match template.split_first() {
+        Some(s) => s,
+        None => return path.is_empty(),
+    };
+
+    match *segment {
+        "{status}" => {
+            if path.first() == Some(&NEW_STATUS) && match_segments(rest, &path[1..], location) {
+                location.is_new = true;
+                return true;
+            }
+
+            location.is_new = false;
+            match_segments(rest, path, location)
+        }
+        "{year}" => match path.first() {
+            Some(y) if y.len() == 4 && y.parse::<i32>().is_ok() => {
+                location.year = y.parse::<i32>().ok();
+                match_segments(rest, &path[1..], location)
+            }
+            _ => false,
+        },
+        // Projects can span several folders, but never a status folder
+        "{project}" => {
+            for end in 1..=path.len() {
+                if path[end - 1] == NEW_STATUS {
+                    break;
+                }
+
+                if match_segments(rest, &path[end..], location) {
+                    location.project = path[..end].join("/");
+                    return true;
+                }
+            }
+
+            false
+        }
+        literal => path.first() == Some(&literal) && match_segments(rest, &path[1..], location),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATES: [&str; 5] = [
+        DEFAULT_TEMPLATE,
+        "{project}/{status}/{name}.md",
+        "{status}/{project}/{year}/{slug}.md",
+        "tasks/{project}/{year}/{status}/{name}.md",
+        "{year}/{status}/{project}/{name}.md",
+    ];
+
+    fn location(project: &str, stem: &str, year: Option<i32>, is_new: bool) -> Location {
+        Location { year, project: project.to_string(), stem: stem.to_string(), is_new }
+    }
+
+    #[test]
+    fn round_trips_every_template() {
+        for template in &TEMPLATES {
+            let year = if has_year(template) { Some(2026) } else { None };
+            for project in &["Inbox", "Clients/Acme", "Clients/Acme/Website"] {
+                for is_new in &[true, false] {
+                    let expected = location(project, "Fix the fence", year, *is_new);
+                    let path = expected.format_with(template);
+                    assert_eq!(Location::parse_with(template, &path), Some(expected), "{} as {}", path, template);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn formats_each_token() {
+        let new = location("Clients/Acme", "fix-the-fence", Some(2026), true);
+        assert_eq!(new.format_with(DEFAULT_TEMPLATE), "2026/Clients/Acme/new/fix-the-fence.md");
+        assert_eq!(new.with_status(false).format_with(DEFAULT_TEMPLATE), "2026/Clients/Acme/fix-the-fence.md");
+        assert_eq!(new.format_with("{status}/{project}/{slug}.md"), "new/Clients/Acme/fix-the-fence.md");
+        assert_eq!(new.format_with("tasks/{year}/{project}/{status}/{name}.md"), "tasks/2026/Clients/Acme/new/fix-the-fence.md");
+    }
+
+    #[test]
+    fn parses_without_year() {
+        let template = "{project}/{status}/{name}.md";
+        assert_eq!(Location::parse_with(template, "Home/new/Fix.md"), Some(location("Home", "Fix", None, true)));
+        assert_eq!(Location::parse_with(template, "2026/Home/Fix.md"), Some(location("2026/Home", "Fix", None, false)));
+    }
+
+    #[test]
+    fn parses_new_at_any_depth() {
+        let template = "{status}/{project}/{year}/{name}.md";
+        assert_eq!(Location::parse_with(template, "new/Clients/Acme/2026/Fix.md"), Some(location("Clients/Acme", "Fix", Some(2026), true)));
+        assert_eq!(Location::parse_with(template, "Clients/Acme/2026/Fix.md"), Some(location("Clients/Acme", "Fix", Some(2026), false)));
+        assert_eq!(Location::parse_with(DEFAULT_TEMPLATE, "2026/Clients/Acme/new/Fix.md"), Some(location("Clients/Acme", "Fix", Some(2026), true)));
+
+        // A project never contains the status folder
+        assert_eq!(Location::parse_with(DEFAULT_TEMPLATE, "2026/Clients/new/Acme/Fix.md"), None);
+        assert_eq!(Location::parse_with(template, "new/new/2026/Fix.md"), None);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(Location::parse_with(DEFAULT_TEMPLATE, "2026/Home/Fix.txt"), None);
+        assert_eq!(Location::parse_with(DEFAULT_TEMPLATE, "Home/Fix.md"), None);
+        assert_eq!(Location::parse_with(DEFAULT_TEMPLATE, "26/Home/Fix.md"), None);
+        assert_eq!(Location::parse_with("tasks/{project}/{status}/{name}.md", "notes/Home/Fix.md"), None);
+    }
+
+    #[test]
+    fn validates_templates() {
+        for template in &TEMPLATES {
+            assert!(validate(template).is_ok(), "{}", template);
+        }
+
+        for template in &[
+            "{year}/{project}/{status}/{name}",
+            "{year}/{project}/{name}.md",
+            "{year}/{status}/{name}.md",
+            "{project}/{project}/{status}/{name}.md",
+            "{year}/{year}/{project}/{status}/{name}.md",
+            "{year}//{project}/{status}/{name}.md",
+            "{month}/{project}/{status}/{name}.md",
+            "new/{project}/{status}/{name}.md",
+            "../{project}/{status}/{name}.md",
+        ] {
+            assert!(validate(template).is_err(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn slugifies_names() {
+        assert_eq!(slugify("Fix the fence!"), "fix-the-fence");
+        assert_eq!(slugify("  Call   Bob -- now "), "call-bob-now");
+        assert_eq!(slugify("Café Über"), "café-über");
+        assert_eq!(slugify("?!"), EMPTY_FILE_NAME);
+        assert_eq!(slugify(&"a".repeat(300)).len(), MAX_FILE_NAME_LENGTH);
+    }
+
+    #[test]
+    fn makes_safe_file_names() {
+        assert_eq!(get_safe_file_name("Fix the fence"), "Fix the fence");
+        assert_eq!(get_safe_file_name("Read a/b: c*d?"), "Read a-b- c-d-");
+        assert_eq!(get_safe_file_name("50% off [draft] (v2)"), "50% off [draft] (v2)");
+        assert_eq!(get_safe_file_name("tab\there a\\b"), "tab-here a-b");
+        assert_eq!(get_safe_file_name("  ..hidden.  "), "hidden");
+        assert_eq!(get_safe_file_name("..."), EMPTY_FILE_NAME);
+        assert_eq!(get_safe_file_name(&"é".repeat(150)).len(), MAX_FILE_NAME_LENGTH);
+        assert_eq!(get_safe_file_name(&format!("a{}", "é".repeat(150))).len(), MAX_FILE_NAME_LENGTH - 1);
+    }
+}
@@ -3,4 +3,22 @@ pub mod task;
 pub mod task_list;
 pub mod setting;
 pub mod reminder;
+pub mod ics;
+pub mod todo_txt;
+pub mod taskwarrior;
+pub mod org;
+pub mod markdown;
+pub mod server;
+pub mod tui;
+pub mod fuzzy;
+pub mod completion;
+pub mod storage;
+pub mod doctor;
+pub mod layout;
+pub mod turnover;
+pub mod project;
+pub mod waiting;
+pub mod plan;
 mod util;
+#[cfg(test)]
+mod fixture;
@@ -0,0 +1,215 @@
+use std::fs;
+use std::io::Result;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use chrono::prelude::*;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+use crate::project;
+use crate::storage;
+use crate::task::Task;
+use crate::task_list::TaskList;
+use crate::util::link;
+
+#[derive(Debug)]
+pub struct ChecklistItem {
+    pub name: String,
+    pub heading: String,
+    pub range: Range<usize>,
+}
+
+// Returns the unchecked task list items that have not already been
+// turned into links, along with the heading they were found under
+pub fn parse_checklist(contents: &str) -> Vec<ChecklistItem> {
+    let mut items: Vec<ChecklistItem> = Vec::new();
+    let mut heading = String::new();
+    let mut in_heading = false;
+    let mut current: Option<ChecklistItem> = None;
+    let mut has_link = false;
+
+    let parser = Parser::new_ext(contents, Options::ENABLE_TASKLISTS);
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading(_)) => {
+                in_heading = true;
+                heading.clear();
+            }
+            Event::End(Tag::Heading(_)) => in_heading = false,
+            Event::TaskListMarker(false) => {
+                has_link = false;
+                current = Some(ChecklistItem {
+                    name: String::new(),
+                    heading: heading.to_string(),
+                    range: range.end..range.end,
+                });
+            }
+            Event::Start(Tag::List(_)) | Event::End(Tag::Item) | Event::End(Tag::Paragraph) => {
+                if let Some(item) = current.take() {
+                    if !has_link && !item.name.trim().is_empty() {
+                        items.push(ChecklistItem { name: item.name.trim().to_string(), ..item });
+                    }
+                }
+            }
+            Event::Text(text) | Event::Code(text) if in_heading => heading.push_str(&text),
+            event => {
+                if let Some(item) = current.as_mut() {
+                    match event {
+                        Event::Text(text) | Event::Code(text) => item.name.push_str(&text),
+                        Event::SoftBreak => item.name.push(' '),
+                        Event::Start(Tag::Link(_, _, _)) => has_link = true,
+                        _ => (),
+                    }
+
+                    item.range.end = item.range.end.max(range.end);
+                }
+            }
+        }
+    }
+
+    items
+}
+
+pub fn import(file: &str, project: &str, list: &str) -> Result<Vec<Task>> {
+    let project = &project::normalize_name(project)?;
+    let contents = fs::read_to_string(file)?;
+    let notes_path = fs::canonicalize(file)?;
+    let notes_folder = notes_path.parent().unwrap_or_else(|| Path::new("/"));
+    let notes_name = notes_path.file_name().and_then(|n| n.to_str()).unwrap_or(file);
+    let year = crate::setting::get_year().unwrap_or_else(|| Local::now().year());
+
+    let mut imported: Vec<Task> = Vec::new();
+    let mut rewritten = contents.to_string();
+
+    // Items are replaced from the end of the file so earlier ranges stay valid
+    for item in parse_checklist(&contents).into_iter().rev() {
+        let task = match Task::get_by_id_or_name(&item.name, false, project) {
+            Ok(t) => t,
+            Err(_e) => {
+                let task = Task::new(&item.name, project, &year);
+                task.add("")?;
+
+                let context = if item.heading.is_empty() {
+                    format!("Imported from {}", notes_name)
+                } else {
+                    format!("Imported from {} under {}", notes_name, item.heading)
+                };
+                task.add_comment(&context, true)?;
+
+                if !list.is_empty() {
+                    if let Err(e) = TaskList::get(list).add(task.clone()) {
+                        eprintln!("imported {} but could not add it to {} with error {}", task.task_name, list, e);
+                    }
+                }
+
+                imported.push(task.clone());
+                task
+            }
+        };
+
+        // Tasks planned in a dry run do not exist yet to be canonicalized
+        let task_path = fs::canonicalize(task.get_file_path())
+            .unwrap_or_else(|_e| PathBuf::from(task.get_file_path()));
+        let path = get_relative_path(notes_folder, &task_path);
+        let path = link::encode(path.to_str().unwrap_or(""));
+        rewritten.replace_range(item.range, &format!(" [{}]({})", link::escape_text(&item.name), path));
+    }
+
+    if rewritten != contents {
+        storage::write(file, &rewritten)?;
+    }
+
+    imported.reverse();
+    Ok(imported)
+}
+
+fn get_relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+
+    for component in &to[common..] {
+        relative.push(component.as_os_str());
+    }
+
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+
+    const NOTES: &str = "# Meeting
+
+- [ ] Call Bob
+- [x] Send minutes
+- [ ] Already [linked](Projects/task.md)
+
+## Follow `up`
+
+* [ ] Book the
+  room
+  1. [ ] Nested item
+- [ ]
+";
+
+    #[test]
+    fn parses_unchecked_items() {
+        let items = parse_checklist(NOTES);
+        let found: Vec<(&str, &str, &str)> = items.iter()
+            .map(|i| (i.name.as_str(), i.heading.as_str(), &NOTES[i.range.clone()]))
+            .collect();
+
+        assert_eq!(found, vec![
+            ("Call Bob", "Meeting", " Call Bob"),
+            ("Book the room", "Follow up", " Book the\n  room"),
+            ("Nested item", "Follow up", " Nested item"),
+        ]);
+    }
+
+    #[test]
+    fn finds_relative_paths() {
+        let cases = [
+            ("/notes", "/notes/Projects/task.md", "Projects/task.md"),
+            ("/notes/2026", "/notes/Projects/task.md", "../Projects/task.md"),
+            ("/home/me/notes", "/srv/tasks/task.md", "../../../srv/tasks/task.md"),
+            ("/notes", "/notes", ""),
+        ];
+
+        for (from, to, expected) in &cases {
+            assert_eq!(get_relative_path(Path::new(from), Path::new(to)), PathBuf::from(expected), "{} to {}", from, to);
+        }
+    }
+
+    #[test]
+    fn rewrites_items_as_links() {
+        let root = Root::new();
+        let existing = root.add_task("Call Bob", "Home");
+        let notes = format!("{}/notes.md", root.path);
+        fs::write(&notes, "## Monday\n\n- [ ] Call Bob\n- [ ] Fix [the] fence\n- [x] Done already\n").unwrap();
+
+        let imported = import(&notes, "Home/", "Today").unwrap();
+        assert_eq!(imported.iter().map(|t| t.task_name.as_str()).collect::<Vec<&str>>(), vec!["Fix [the] fence"]);
+        assert_eq!(Task::get_by_id_or_name("Call Bob", false, "").unwrap().id, existing.id);
+
+        assert_eq!(fs::read_to_string(&notes).unwrap(), "## Monday\n\n\
+            - [ ] [Call Bob](Projects/2026/Home/new/Call%20Bob.md)\n\
+            - [ ] [Fix \\[the\\] fence](Projects/2026/Home/Fix%20%5Bthe%5D%20fence.md)\n\
+            - [x] Done already\n");
+
+        let comments = Task::get_by_id_or_name("Fix [the] fence", false, "").unwrap().get_comments().unwrap();
+        assert!(comments.iter().any(|(_, c)| c == "Imported from notes.md under Monday"));
+        assert_eq!(TaskList::get("Today").get_tasks().unwrap()[0].task_name, "Fix [the] fence");
+
+        assert!(import(&notes, "Home", "").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+extern crate chrono;
+
+use chrono::prelude::*;
+use std::io::Result;
+
+use crate::task::Task;
+
+pub fn export(tasks: &[Task], title: &str) -> Result<String> {
+    let mut projects: Vec<(&str, Vec<&Task>)> = Vec::new();
+    for task in tasks {
+        match projects.iter_mut().find(|(p, _)| *p == task.project) {
+            Some((_, project_tasks)) => project_tasks.push(task),
+            None => projects.push((&task.project, vec![task])),
+        }
+    }
+
+    let mut lines: Vec<String> = vec![format!("#+TITLE: {}", title), "".to_string()];
+    for (project, project_tasks) in projects {
+        lines.push(format!("* {}", project));
+
+        for task in project_tasks {
+            lines.append(&mut get_headline(task)?);
+        }
+    }
+
+    Ok(lines.iter().map(|l| format!("{}\n", l)).collect())
+}
+
+fn get_headline(task: &Task) -> Result<Vec<String>> {
+    let keyword = if task.is_finished() { "DONE" } else { "TODO" };
+    let mut lines = vec![format!("** {} {}", keyword, task.task_name)];
+
+    if let Some(timestamp) = get_planning(task) {
+        lines.push(timestamp);
+    }
+
+    lines.push(":PROPERTIES:".to_string());
+    lines.push(format!(":ID: {}", task.id));
+    lines.push(format!(":PATH: {}", task.path));
+    lines.push(format!(":CREATED: {}", format_timestamp(&task.created.naive_local(), true, false)));
+    lines.push(":END:".to_string());
+
+    for (date, comment) in task.get_comments()? {
+        let mut comment_lines = comment.lines();
+        lines.push(format!(
+            "- {} {}",
+            format_timestamp(&date.naive_local(), true, false),
+            comment_lines.next().unwrap_or("")
+        ));
+
+        for line in comment_lines {
+            lines.push(format!("  {}", line));
+        }
+    }
+
+    Ok(lines)
+}
+
+// Reminders with advance notice become deadlines with a matching warning
+// period, everything else is scheduled for the reminder date
+fn get_planning(task: &Task) -> Option<String> {
+    let remind = task.remind.as_ref()?;
+    let date = remind.get_next_date(Local::now().date_naive())?;
+    let datetime = match remind.get_time() {
+        Some(t) => date.and_time(t),
+        None => date.and_hms_opt(0, 0, 0)?,
+    };
+
+    let mut timestamp = format_timestamp(&datetime, remind.get_time().is_some(), true);
+    timestamp.pop();
+
+    if remind.get_date().is_none() {
+        if remind.month.is_empty() && remind.day.is_empty() {
+            timestamp.push_str(" +1d");
+        } else if remind.month.is_empty() {
+            timestamp.push_str(" +1m");
+        } else {
+            timestamp.push_str(" +1y");
+        }
+    }
+
+    if remind.notice > 0 {
+        Some(format!("DEADLINE: {} -{}d>", timestamp, remind.notice))
+    } else {
+        Some(format!("SCHEDULED: {}>", timestamp))
+    }
+}
+
+fn format_timestamp(date: &NaiveDateTime, with_time: bool, active: bool) -> String {
+    let format = if with_time { "%Y-%m-%d %a %H:%M" } else { "%Y-%m-%d %a" };
+    let (open, close) = if active { ('<', '>') } else { ('[', ']') };
+
+    format!("{}{}{}", open, date.format(format), close)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+    use crate::reminder::Reminder;
+
+    fn with_reminder(month: &str, day: &str, year: &str, time: &str, notice: u32) -> Task {
+        let mut task = Task::new("Call Bob", "Home", &2026);
+        task.remind = Some(Reminder::new(month, day, year, time, &notice));
+        task
+    }
+
+    #[test]
+    fn plans_dated_reminders() {
+        let _root = Root::new();
+        assert_eq!(get_planning(&Task::new("Call Bob", "Home", &2026)), None);
+        assert_eq!(get_planning(&with_reminder("10", "20", "2026", "", 0)), Some("SCHEDULED: <2026-10-20 Tue>".to_string()));
+        assert_eq!(get_planning(&with_reminder("10", "20", "2026", "15:30", 0)), Some("SCHEDULED: <2026-10-20 Tue 15:30>".to_string()));
+        assert_eq!(get_planning(&with_reminder("10", "20", "2026", "", 3)), Some("DEADLINE: <2026-10-20 Tue -3d>".to_string()));
+    }
+
+    #[test]
+    fn plans_repeating_reminders() {
+        let _root = Root::new();
+        let today = Local::now().date_naive();
+        let next = |task: &Task| task.remind.as_ref().unwrap().get_next_date(today).unwrap();
+
+        let daily = with_reminder("", "", "", "09:00", 0);
+        let expected = format!("SCHEDULED: <{} 09:00 +1d>", next(&daily).format("%Y-%m-%d %a"));
+        assert_eq!(get_planning(&daily), Some(expected));
+
+        let monthly = with_reminder("", "15", "", "", 0);
+        let expected = format!("SCHEDULED: <{} +1m>", next(&monthly).format("%Y-%m-%d %a"));
+        assert_eq!(get_planning(&monthly), Some(expected));
+
+        let yearly = with_reminder("12", "25", "", "", 2);
+        let expected = format!("DEADLINE: <{} +1y -2d>", next(&yearly).format("%Y-%m-%d %a"));
+        assert_eq!(get_planning(&yearly), Some(expected));
+    }
+
+    #[test]
+    fn exports_headlines() {
+        let _root = Root::new();
+        let created = Local.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap();
+        let mut open = Task::new("Call Bob", "Home", &2026);
+        open.created = created;
+        open.remind = Some(Reminder::new("10", "20", "2026", "", &0));
+        open.add("").unwrap();
+        open.add_comment_at("left a message\nsecond line", true, Local.with_ymd_and_hms(2026, 10, 6, 14, 30, 0).unwrap()).unwrap();
+
+        let mut done = Task::new("Pay rent", "Home", &2026);
+        done.created = created;
+        done.add("").unwrap();
+        done.finish_at("Task Completed", Local.with_ymd_and_hms(2026, 10, 7, 8, 0, 0).unwrap()).unwrap();
+
+        let mut other = Task::new("Write report", "Work", &2026);
+        other.created = created;
+        other.add("").unwrap();
+
+        let exported = export(&[open.clone(), other.clone(), done.clone()], "Tasks").unwrap();
+        let expected = format!("#+TITLE: Tasks
+
+* Home
+** TODO Call Bob
+SCHEDULED: <2026-10-20 Tue>
+:PROPERTIES:
+:ID: {}
+:PATH: {}
+:CREATED: [2026-10-05 Mon 09:00]
+:END:
+- [2026-10-06 Tue 14:30] left a message
+  second line
+- [2026-10-05 Mon 09:00] Task Created
+** DONE Pay rent
+:PROPERTIES:
+:ID: {}
+:PATH: {}
+:CREATED: [2026-10-05 Mon 09:00]
+:END:
+- [2026-10-07 Wed 08:00] Task Completed
+- [2026-10-05 Mon 09:00] Task Created
+* Work
+** TODO Write report
+:PROPERTIES:
+:ID: {}
+:PATH: {}
+:CREATED: [2026-10-05 Mon 09:00]
+:END:
+- [2026-10-05 Mon 09:00] Task Created
+", open.id, open.path, done.id, done.path, other.id, other.path);
+
+        assert_eq!(exported, expected);
+    }
+}
@@ -0,0 +1,163 @@
+use std::io::Result;
+
+use chrono::prelude::*;
+
+use crate::journal::Journal;
+use crate::task::Task;
+use crate::task_list::{self, TaskList, TODAY_LIST};
+use crate::waiting;
+
+pub const QUEUED_LIST: &str = "Queued";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+    Today,
+    Reminder,
+    FollowUp,
+    Queued,
+}
+
+pub struct Candidate {
+    pub task: Task,
+    pub reason: Reason,
+}
+
+// Everything that could be on Today, in the order it is offered: what is
+// left on Today, tasks with a reminder or follow up today, then Queued
+pub fn get_candidates(today: NaiveDate) -> Result<Vec<Candidate>> {
+    let mut candidates: Vec<Candidate> = TaskList::get(TODAY_LIST)
+        .get_tasks()?
+        .into_iter()
+        .map(|task| Candidate { task, reason: Reason::Today })
+        .collect();
+
+    for task in Task::get_all(false, "")? {
+        let lists = task_list::get_lists_for_task(&task.task_name);
+        if lists.iter().any(|l| l == TODAY_LIST) || task.is_finished() {
+            continue;
+        }
+
+        let has_reminder = task.remind.as_ref().and_then(|r| r.get_next_date(today)) == Some(today);
+        let is_following_up = lists.iter().any(|l| l == waiting::WAITING_LIST)
+            && matches!(waiting::get_follow_up(&task, today), waiting::FollowUp::Due | waiting::FollowUp::Overdue);
+
+        if has_reminder {
+            candidates.push(Candidate { task, reason: Reason::Reminder });
+        } else if is_following_up {
+            candidates.push(Candidate { task, reason: Reason::FollowUp });
+        }
+    }
+
+    if task_list::get_names().iter().any(|l| l == QUEUED_LIST) {
+        for task in TaskList::get(QUEUED_LIST).get_tasks()? {
+            if !candidates.iter().any(|c| c.task.task_name == task.task_name) {
+                candidates.push(Candidate { task, reason: Reason::Queued });
+            }
+        }
+    }
+
+    Ok(candidates)
+}
+
+// Deferred Today tasks go back to Queued, accepted tasks are added to Today.
+// The journal gets a list of what stays on Today, unless an earlier plan
+// wrote one, and adding to Today logs the rest below it
+pub fn write(decisions: Vec<(Candidate, bool)>, force: bool) -> Result<Vec<(String, Result<()>)>> {
+    let mut results: Vec<(String, Result<()>)> = Vec::new();
+
+    for (candidate, _accepted) in decisions.iter().filter(|(c, a)| c.reason == Reason::Today && !a) {
+        results.push((candidate.task.task_name.to_string(), defer(&candidate.task)));
+    }
+
+    let journal = Journal::new("Journal", "My Thoughts Today")?;
+    journal.create()?;
+    if !journal.has_task_list() {
+        journal.add_tasks_to_journal(TaskList::get(TODAY_LIST).get_tasks()?);
+    }
+
+    let today = TaskList::get(TODAY_LIST);
+    for (candidate, _accepted) in decisions.iter().filter(|(c, a)| c.reason != Reason::Today && *a) {
+        let result = match candidate.reason {
+            Reason::FollowUp => waiting::follow_up(&candidate.task, force),
+            _ if force => today.add_over_limit(candidate.task.clone()),
+            _ => today.add(candidate.task.clone()),
+        };
+
+        results.push((candidate.task.task_name.to_string(), result));
+    }
+
+    Ok(results)
+}
+
+fn defer(task: &Task) -> Result<()> {
+    match task_list::get_names().iter().any(|l| l == QUEUED_LIST) {
+        true => TaskList::get(QUEUED_LIST).add_over_limit(task.clone()),
+        false => task.unlist(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+
+    fn get_found(today: NaiveDate) -> Vec<(String, Reason)> {
+        get_candidates(today)
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.task.task_name, c.reason))
+            .collect()
+    }
+
+    #[test]
+    fn offers_candidates_in_order() {
+        let root = Root::new();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let add_to = |list: &str, task_name: &str| {
+            let task = root.add_task(task_name, "Home");
+            TaskList::get(list).add(task.clone()).unwrap();
+            task
+        };
+
+        add_to(QUEUED_LIST, "Queued task");
+        add_to(TODAY_LIST, "Left on Today");
+        root.add_task("Reminder today", "Home").set_reminder("10", "19", "2026", "", &0).unwrap();
+        root.add_task("Reminder tomorrow", "Home").set_reminder("10", "20", "2026", "", &0).unwrap();
+        root.add_task("Every day", "Home").set_reminder("", "", "", "09:00", &0).unwrap();
+        add_to(QUEUED_LIST, "Queued with reminder").set_reminder("10", "19", "2026", "", &0).unwrap();
+
+        let finished = root.add_task("Finished", "Home");
+        finished.set_reminder("10", "19", "2026", "", &0).unwrap();
+        finished.finish("Task Completed").unwrap();
+
+        let yesterday = today.pred_opt().unwrap();
+        root.add_task("Overdue", "Work").wait("Alice", Some(yesterday), false).unwrap();
+        root.add_task("Due", "Work").wait("Bob", Some(today), false).unwrap();
+        root.add_task("Later", "Work").wait("Carol", today.succ_opt(), false).unwrap();
+        root.add_task("No follow up", "Work").wait("Dan", None, false).unwrap();
+
+        let mut found = get_found(today);
+        assert_eq!(found.remove(0), ("Left on Today".to_string(), Reason::Today));
+        assert_eq!(found.pop(), Some(("Queued task".to_string(), Reason::Queued)));
+
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(found, vec![
+            ("Due".to_string(), Reason::FollowUp),
+            ("Every day".to_string(), Reason::Reminder),
+            ("Overdue".to_string(), Reason::FollowUp),
+            ("Queued with reminder".to_string(), Reason::Reminder),
+            ("Reminder today".to_string(), Reason::Reminder),
+        ]);
+    }
+
+    #[test]
+    fn skips_queued_without_the_list() {
+        let root = Root::new();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        TaskList::get(QUEUED_LIST).add(root.add_task("Queued task", "Home")).unwrap();
+        TaskList::get(TODAY_LIST).add(root.add_task("Left on Today", "Home")).unwrap();
+
+        std::env::set_var("TODUIT_TODO_LISTS", "Today,Waiting");
+        assert_eq!(get_found(today), vec![("Left on Today".to_string(), Reason::Today)]);
+    }
+}
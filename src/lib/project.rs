@@ -0,0 +1,553 @@
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::layout;
+use crate::layout::Location;
+use crate::storage;
+use crate::task::Task;
+use crate::task_list;
+
+const METADATA_FOLDER_NAME: &str = ".projects";
+
+// Projects are the folders tasks are filed under. The metadata file is
+// optional and kept apart from the task files, so every layout can use it
+#[derive(Debug, Default)]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub name: String,
+    pub description: Option<String>,
+    pub owner: Option<String>,
+    pub default_list: Option<String>,
+    pub color: Option<String>,
+    pub archived: Option<bool>,
+}
+
+pub struct ProjectCount {
+    pub name: String,
+    pub open: usize,
+    pub done: usize,
+    pub archived: bool,
+}
+
+impl Project {
+    pub fn new(name: &str) -> Project {
+        Project {
+            name: name.to_string(),
+            ..Project::default()
+        }
+    }
+
+    pub fn get(name: &str) -> Result<Project> {
+        let contents = storage::read_to_string(get_metadata_path(name))?;
+        serde_yaml::from_str(&contents).map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))
+    }
+
+    pub fn is_archived(&self) -> bool {
+        self.archived == Some(true)
+    }
+
+    pub fn create(&self) -> Result<()> {
+        validate_name(&self.name)?;
+
+        let metadata_path = get_metadata_path(&self.name);
+        if storage::exists(&metadata_path) {
+            return Err(Error::new(ErrorKind::AlreadyExists, format!("project {} already exists", self.name)));
+        }
+
+        if let Some(list) = &self.default_list {
+            if !task_list::get_names().contains(list) {
+                return Err(Error::new(ErrorKind::InvalidInput, format!("{} is not a todo list", list)));
+            }
+        }
+
+        self.save()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let metadata_path = get_metadata_path(&self.name);
+        if let Some(folder) = Path::new(&metadata_path).parent() {
+            storage::create_dir_all(folder)?;
+        }
+
+        let yml = serde_yaml::to_string(self).map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+        storage::write(&metadata_path, &yml)
+    }
+
+    // Moves every task of the project and the projects below it, in every
+    // year, and points the lists at the new files. Nothing is moved when any
+    // file is in the way
+    pub fn rename(old_name: &str, new_name: &str) -> Result<usize> {
+        let old_name = &normalize_name(old_name)?;
+        let new_name = &normalize_name(new_name)?;
+        if is_within(new_name, old_name) {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("{} can not be moved into itself", old_name)));
+        }
+
+        let files = get_project_files(old_name);
+        let metadata = get_area_metadata(old_name)?;
+        if files.is_empty() && metadata.is_empty() {
+            return Err(Error::new(ErrorKind::NotFound, format!("no project named {}", old_name)));
+        }
+
+        let rename = |project: &str| match project.strip_prefix(old_name.as_str()) {
+            Some(rest) => format!("{}{}", new_name, rest),
+            None => project.to_string(),
+        };
+        for project in &metadata {
+            if storage::exists(get_metadata_path(&rename(&project.name))) {
+                return Err(Error::new(ErrorKind::AlreadyExists, format!("project {} already exists", rename(&project.name))));
+            }
+        }
+
+        let moves: Vec<(String, Location)> = files
+            .into_iter()
+            .map(|(f, l)| {
+                let project = rename(&l.project);
+                (f, Location { project, ..l })
+            })
+            .collect();
+
+        for (_f, location) in &moves {
+            let new_path = location.get_file_path();
+            if storage::exists(&new_path) {
+                return Err(Error::new(ErrorKind::AlreadyExists, format!("{} already exists", new_path)));
+            }
+        }
+
+        for (f_path, location) in &moves {
+            let new_path = location.get_file_path();
+            if let Some(folder) = Path::new(&new_path).parent() {
+                storage::create_dir_all(folder)?;
+            }
+
+            storage::rename(f_path, &new_path)?;
+
+            let mut task = Task::get(&new_path)?;
+            task.project = location.project.to_string();
+            task.path = location.with_status(false).get_relative_path();
+
+            let task_link = task_list::get_task_link(&task);
+            for list in task_list::get_lists_for_task(&task.task_name) {
+                storage::write(task_list::get_entry_path(&list, &task.task_name), &task_link)?;
+            }
+
+            task.save()?;
+        }
+
+        for mut project in metadata {
+            let old_path = get_metadata_path(&project.name);
+            project.name = rename(&project.name);
+            project.save()?;
+            storage::remove_file(&old_path)?;
+        }
+
+        Ok(moves.len())
+    }
+
+    // Archived tasks are moved out of the project folder, which every query
+    // looks in, and off the lists. Their paths are kept, so moving a file
+    // back restores it. Archiving an area archives every project below it
+    pub fn archive(name: &str) -> Result<usize> {
+        let name = &normalize_name(name)?;
+        let files = get_project_files(name);
+        let mut metadata = get_area_metadata(name)?;
+        if files.is_empty() && metadata.is_empty() {
+            return Err(Error::new(ErrorKind::NotFound, format!("no project named {}", name)));
+        }
+
+        if !metadata.iter().any(|p| p.name == *name) {
+            metadata.push(Project::new(name));
+        }
+
+        let prefix = format!("{}/", crate::setting::get_project_folder());
+        let get_archive_path = |f_path: &str| format!(
+            "{}/{}",
+            crate::setting::get_archive_folder(),
+            f_path.trim_start_matches(&prefix)
+        );
+
+        for (f_path, _l) in &files {
+            let archive_path = get_archive_path(f_path);
+            if storage::exists(&archive_path) {
+                return Err(Error::new(ErrorKind::AlreadyExists, format!("{} already exists", archive_path)));
+            }
+        }
+
+        for (f_path, _l) in &files {
+            let task = Task::get(f_path)?;
+            task_list::remove_from_lists(&task, "none")?;
+
+            let archive_path = get_archive_path(f_path);
+            if let Some(folder) = Path::new(&archive_path).parent() {
+                storage::create_dir_all(folder)?;
+            }
+
+            storage::rename(f_path, &archive_path)?;
+        }
+
+        for mut project in metadata {
+            project.archived = Some(true);
+            project.save()?;
+        }
+
+        Ok(files.len())
+    }
+}
+
+// Every project with tasks or a metadata file, and every area above them,
+// with the number of open and finished tasks including the projects below.
+// Archived projects only have their metadata left
+pub fn get_counts() -> Result<Vec<ProjectCount>> {
+    let mut counts: BTreeMap<String, ProjectCount> = BTreeMap::new();
+
+    for project in get_metadata()? {
+        let count = ProjectCount {
+            name: project.name.to_string(),
+            open: 0,
+            done: 0,
+            archived: project.is_archived(),
+        };
+        counts.insert(project.name, count);
+    }
+
+    for task in Task::get_all(false, "")? {
+        let is_finished = task.is_finished();
+        for name in get_ancestors(&task.project) {
+            let count = counts.entry(name.to_string()).or_insert_with(|| ProjectCount {
+                name,
+                open: 0,
+                done: 0,
+                archived: false,
+            });
+
+            if is_finished {
+                count.done += 1;
+            } else {
+                count.open += 1;
+            }
+        }
+    }
+
+    Ok(counts.into_values().collect())
+}
+
+pub fn get_names() -> Vec<String> {
+    let mut names: Vec<String> = layout::get_task_files()
+        .into_iter()
+        .map(|(_f, l)| l.project)
+        .chain(get_metadata().unwrap_or_default().into_iter().filter(|p| !p.is_archived()).map(|p| p.name))
+        .collect();
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn get_metadata() -> Result<Vec<Project>> {
+    let metadata_folder = format!("{}/{}", crate::setting::get_project_folder(), METADATA_FOLDER_NAME);
+    let mut projects: Vec<Project> = Vec::new();
+
+    for entry in WalkDir::new(&metadata_folder)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok()) {
+            let file = match entry.path().to_str() {
+                Some(f) if f.ends_with(".yml") && storage::exists(f) => f,
+                _ => continue,
+            };
+
+            let contents = storage::read_to_string(file)?;
+            match serde_yaml::from_str::<Project>(&contents) {
+                Ok(p) => projects.push(p),
+                Err(e) => eprintln!("could not read {} with error {}", file, e),
+            }
+    }
+
+    Ok(projects)
+}
+
+// Projects that file new tasks onto a list follow it when it is renamed,
+// and go back to no list when it is deleted
+pub fn rename_default_list(list: &str, new_list: Option<&str>) -> Result<usize> {
+    let mut updated = 0;
+    for mut project in get_metadata()? {
+        if project.default_list.as_deref() == Some(list) {
+            project.default_list = new_list.map(|l| l.to_string());
+            project.save()?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+fn get_metadata_path(name: &str) -> String {
+    format!("{}/{}/{}.yml", crate::setting::get_project_folder(), METADATA_FOLDER_NAME, name)
+}
+
+fn get_area_metadata(area: &str) -> Result<Vec<Project>> {
+    Ok(get_metadata()?.into_iter().filter(|p| is_within(&p.name, area)).collect())
+}
+
+fn get_project_files(area: &str) -> Vec<(String, Location)> {
+    layout::get_task_files()
+        .into_iter()
+        .filter(|(_f, l)| is_within(&l.project, area))
+        .collect()
+}
+
+// Projects can be nested into areas, like `Clients/Acme/Website`. Stray
+// slashes are dropped so a typo does not create a new level
+pub fn normalize_name(name: &str) -> Result<String> {
+    let segments: Vec<&str> = name.split('/').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
+    let invalid = segments.is_empty()
+        || segments.iter().any(|s| *s == "." || *s == ".." || *s == "new" || s.starts_with('.'));
+
+    match invalid {
+        true => Err(Error::new(ErrorKind::InvalidInput, format!("{:?} is not a valid project name", name))),
+        false => Ok(segments.join("/")),
+    }
+}
+
+// Imported tasks go to the project given for the import, or else the one
+// they came with, which falls back to Inbox when it can not be used
+pub fn get_import_name(project: &str, imported: Option<&str>) -> Result<String> {
+    if !project.is_empty() {
+        return normalize_name(project);
+    }
+
+    match imported.filter(|p| !p.trim().is_empty()) {
+        Some(p) => normalize_name(p).or_else(|e| {
+            eprintln!("{}, using Inbox", e);
+            Ok("Inbox".to_string())
+        }),
+        None => Ok("Inbox".to_string()),
+    }
+}
+
+// Every level of a project from the top area down, ending with the project
+pub fn get_ancestors(name: &str) -> Vec<String> {
+    let segments: Vec<&str> = name.split('/').collect();
+    (1..=segments.len()).map(|end| segments[..end].join("/")).collect()
+}
+
+pub fn is_within(name: &str, area: &str) -> bool {
+    let area = area.trim_end_matches('/');
+    name == area || name.starts_with(&format!("{}/", area))
+}
+
+// Matches a project against a pattern folder by folder, where `*` stands
+// for one folder and `**` for any number of them, so `Clients/**` is the
+// area and everything below it
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_end_matches('/').split('/').collect();
+    let name: Vec<&str> = name.split('/').collect();
+    match_segments(&pattern, &name)
+}
+
+fn match_segments(pattern: &[&str], name: &[&str]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, _) => name.is_empty(),
+        (Some((&"**", rest)), _) => (0..=name.len()).any(|i| match_segments(rest, &name[i..])),
+        (Some((segment, rest)), Some((folder, name_rest))) => {
+            (*segment == "*" || segment == folder) && match_segments(rest, name_rest)
+        }
+        (Some(_), None) => false,
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<()> {
+    match normalize_name(name)? == name {
+        true => Ok(()),
+        false => Err(Error::new(ErrorKind::InvalidInput, format!("{:?} is not a valid project name", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+    use crate::task_list::TaskList;
+
+    #[test]
+    fn normalizes_names() {
+        assert_eq!(normalize_name("Clients/Acme").unwrap(), "Clients/Acme");
+        assert_eq!(normalize_name("/Clients//Acme/").unwrap(), "Clients/Acme");
+        assert_eq!(normalize_name(" Clients / Acme ").unwrap(), "Clients/Acme");
+        assert_eq!(normalize_name("Work/").unwrap(), "Work");
+    }
+
+    #[test]
+    fn rejects_names() {
+        for name in &["", "/", " / ", "..", "Clients/../Acme", "Clients/.", ".hidden", "Clients/new"] {
+            assert!(normalize_name(name).is_err(), "{:?} should be rejected", name);
+        }
+        assert!(validate_name("Work/").is_err());
+        assert!(validate_name("Work").is_ok());
+    }
+
+    #[test]
+    fn matches_single_folders() {
+        assert!(matches("Clients/Acme", "Clients/Acme"));
+        assert!(matches("Clients/*", "Clients/Acme"));
+        assert!(matches("*/Website", "Clients/Website"));
+        assert!(!matches("Clients/*", "Clients"));
+        assert!(!matches("Clients/*", "Clients/Acme/Website"));
+        assert!(!matches("Clients", "ClientsX"));
+        assert!(!matches("Clients/*", "ClientsX/Acme"));
+    }
+
+    #[test]
+    fn matches_any_folders() {
+        assert!(matches("**", "Clients"));
+        assert!(matches("**/Website", "Website"));
+        assert!(matches("**/Website", "Clients/Acme/Website"));
+        assert!(!matches("**/Website", "Clients/Website/Old"));
+        assert!(matches("Clients/**/Website", "Clients/Website"));
+        assert!(matches("Clients/**/Website", "Clients/Acme/Beta/Website"));
+        assert!(!matches("Clients/**/Website", "ClientsX/Acme/Website"));
+        assert!(matches("Clients/**", "Clients"));
+        assert!(matches("Clients/**", "Clients/Acme/Website"));
+        assert!(!matches("Clients/**", "ClientsX"));
+    }
+
+    #[test]
+    fn matches_with_trailing_slash() {
+        assert!(matches("Clients/", "Clients"));
+        assert!(matches("Clients/*/", "Clients/Acme"));
+        assert!(!matches("Clients/", "Clients/Acme"));
+    }
+
+    #[test]
+    fn matches_segments() {
+        assert!(match_segments(&[], &[]));
+        assert!(!match_segments(&[], &["Clients"]));
+        assert!(match_segments(&["**"], &[]));
+        assert!(match_segments(&["**", "**"], &["Clients", "Acme"]));
+        assert!(!match_segments(&["*"], &[]));
+    }
+
+    #[test]
+    fn lists_ancestors() {
+        assert_eq!(get_ancestors("Clients/Acme/Website"), vec!["Clients", "Clients/Acme", "Clients/Acme/Website"]);
+        assert_eq!(get_ancestors("Inbox"), vec!["Inbox"]);
+    }
+
+    #[test]
+    fn checks_areas() {
+        assert!(is_within("Clients", "Clients"));
+        assert!(is_within("Clients/Acme", "Clients"));
+        assert!(is_within("Clients/Acme/Website", "Clients/Acme"));
+        assert!(!is_within("ClientsX", "Clients"));
+        assert!(!is_within("ClientsX/Acme", "Clients"));
+        assert!(!is_within("Clients", "Clients/Acme"));
+    }
+
+    #[test]
+    fn checks_areas_with_trailing_slash() {
+        assert!(is_within("Clients", "Clients/"));
+        assert!(is_within("Clients/Acme", "Clients/"));
+        assert!(!is_within("ClientsX", "Clients/"));
+    }
+
+    #[test]
+    fn names_imported_projects() {
+        assert_eq!(get_import_name("", Some("Work/")).unwrap(), "Work");
+        assert_eq!(get_import_name("", Some("Clients//Acme")).unwrap(), "Clients/Acme");
+        assert_eq!(get_import_name("", Some(".hidden")).unwrap(), "Inbox");
+        assert_eq!(get_import_name("", Some(" ")).unwrap(), "Inbox");
+        assert_eq!(get_import_name("", None).unwrap(), "Inbox");
+        assert_eq!(get_import_name("Home/", Some("Work")).unwrap(), "Home");
+        assert!(get_import_name("..", Some("Work")).is_err());
+    }
+
+    #[test]
+    fn renames_with_trailing_slash() {
+        let root = Root::new();
+        root.add_task("Plan the week", "Work");
+
+        assert_eq!(Project::rename("Work/", "Job/").unwrap(), 1);
+
+        let task = Task::get_by_id_or_name("Plan the week", false, "").unwrap();
+        assert_eq!(task.project, "Job");
+        assert_eq!(task.path, "Projects/2026/Job/Plan the week.md");
+        assert!(root.exists("Projects/2026/Job/new/Plan the week.md"));
+        assert!(!root.exists("Projects/2026/Work/new/Plan the week.md"));
+    }
+
+    #[test]
+    fn renames_nested_projects() {
+        let root = Root::new();
+        let task = root.add_task("Launch site", "Clients/Acme/Website");
+        root.add_task("Send invoice", "Clients");
+        root.add_task("Other client", "ClientsX");
+        TaskList::get("Today").add(task).unwrap();
+
+        assert_eq!(Project::rename("Clients/", "Partners").unwrap(), 2);
+
+        let task = Task::get_by_id_or_name("Launch site", false, "").unwrap();
+        assert_eq!(task.project, "Partners/Acme/Website");
+        assert!(root.exists("Projects/2026/Partners/Acme/Website/Launch site.md"));
+        assert_eq!(Task::get_by_id_or_name("Send invoice", false, "").unwrap().project, "Partners");
+        assert_eq!(Task::get_by_id_or_name("Other client", false, "").unwrap().project, "ClientsX");
+
+        let today: Vec<String> = TaskList::get("Today").get_tasks().unwrap().into_iter().map(|t| t.project).collect();
+        assert_eq!(today, vec!["Partners/Acme/Website"]);
+    }
+
+    #[test]
+    fn renames_metadata() {
+        let _root = Root::new();
+        Project::new("Clients/Acme").create().unwrap();
+
+        assert_eq!(Project::rename("Clients", "Partners").unwrap(), 0);
+        assert!(Project::get("Partners/Acme").is_ok());
+        assert!(Project::get("Clients/Acme").is_err());
+    }
+
+    #[test]
+    fn does_not_rename_into_itself_or_over_others() {
+        let _root = Root::new();
+        let task = Task::new("Launch site", "Clients/Acme", &2026);
+        task.add("").unwrap();
+        Task::new("Launch site", "Partners/Acme", &2026).add("").unwrap();
+
+        assert!(Project::rename("Clients", "Clients/Old").is_err());
+        assert!(Project::rename("Clients/", "Partners").is_err());
+        assert!(Project::rename("Nobody", "Somebody").is_err());
+        assert!(Project::rename("..", "Somebody").is_err());
+        assert!(Task::get_by_path(&task.path).is_ok());
+    }
+
+    #[test]
+    fn archives_with_trailing_slash() {
+        let root = Root::new();
+        let task = root.add_task("Launch site", "Clients/Acme");
+        root.add_task("Other client", "ClientsX");
+        TaskList::get("Today").add(task).unwrap();
+
+        assert_eq!(Project::archive("Clients/").unwrap(), 1);
+
+        assert!(root.exists("Archive/2026/Clients/Acme/Launch site.md"));
+        assert!(!root.exists("Projects/2026/Clients/Acme/Launch site.md"));
+        assert!(Project::get("Clients").unwrap().is_archived());
+        assert!(!root.exists("Projects/.projects/Clients/.yml"));
+        assert!(TaskList::get("Today").get_tasks().unwrap().is_empty());
+        assert!(Task::get_by_id_or_name("Other client", false, "").is_ok());
+    }
+
+    #[test]
+    fn checks_default_list() {
+        let _root = Root::new();
+        let mut project = Project::new("Work");
+        project.default_list = Some("Later".to_string());
+        assert!(project.create().is_err());
+
+        project.default_list = Some("Someday".to_string());
+        assert!(project.create().is_ok());
+    }
+}
@@ -3,10 +3,11 @@ extern crate num_traits;
 
 use num_traits::cast::FromPrimitive;
 use chrono::prelude::*;
+use chrono::Duration;
 use serde::{Deserialize, Serialize};
-use std::fs::{OpenOptions};
 use std::io::Result;
-use std::io::Write;
+
+use crate::storage;
 
 #[derive(Debug)]
 #[derive(Clone, Serialize, Deserialize)]
@@ -33,18 +34,13 @@ impl Reminder {
     pub fn create(&self, task_name: &str) -> Result<()> {
         let reminder = &self.clone();
         let reminder_file_path = crate::setting::get_reminder_file();
-        let mut reminder_file = OpenOptions::new()
-            .append(true)
-            .open(&reminder_file_path)
-            .expect("could not open reminder file");
-
         let date_value = &self.get_reminder_date();
         let date = match date_value {
             Ok(d) => d,
             Err(_e) => ""
         };
 
-        let reminder_time = if reminder.time != "" { format!("AT {}", reminder.time) } else { "".to_string() };
+        let reminder_time = if !reminder.time.is_empty() { format!("AT {}", reminder.time) } else { "".to_string() };
 
         let notice = &reminder.notice.to_string();
         let reminder_notice = if reminder.notice > 0 { format!("-{}", notice) } else { "".to_string() };
@@ -55,18 +51,50 @@ impl Reminder {
            task_name
         );
 
-        let run_entry = format!("REM {} {} {} RUN ({}) & \n",
+        let run_entry = format!("REM {} {} {} RUN (toduit add \"{}\" Today) & \n",
             date,
             reminder_time,
             reminder_notice,
-            format!("toduit add \"{}\" Today", task_name)
+            task_name
         );
 
-        reminder_file.write_all(rem_entry.as_bytes())?;
-        reminder_file.write_all(run_entry.as_bytes())?;
-        reminder_file.sync_data()?;
+        storage::append(&reminder_file_path, &format!("{}{}", rem_entry, run_entry))
+    }
+
+    pub fn get_date(&self) -> Option<NaiveDate> {
+        let month = self.month.parse::<u32>().ok()?;
+        let day = self.day.parse::<u32>().ok()?;
+        let year = self.year.parse::<i32>().ok()?;
+
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
 
-        Ok(())
+    pub fn get_time(&self) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(&self.time, "%H:%M").ok()
+    }
+
+    // Reminders without a year, month or day repeat the way remind
+    // treats them, so this returns the first occurrence on or after `from`
+    pub fn get_next_date(&self, from: NaiveDate) -> Option<NaiveDate> {
+        if let Some(date) = self.get_date() {
+            return Some(date);
+        }
+
+        let month = self.month.parse::<u32>().ok();
+        let day = self.day.parse::<u32>().ok();
+        match (month, day) {
+            (Some(m), Some(d)) => NaiveDate::from_ymd_opt(from.year(), m, d)
+                .filter(|date| *date >= from)
+                .or_else(|| NaiveDate::from_ymd_opt(from.year() + 1, m, d)),
+            (None, Some(d)) => NaiveDate::from_ymd_opt(from.year(), from.month(), d)
+                .filter(|date| *date >= from)
+                .or_else(|| {
+                    let next = from.with_day(1)? + Duration::days(32);
+                    NaiveDate::from_ymd_opt(next.year(), next.month(), d)
+                }),
+            (None, None) if !self.time.is_empty() => Some(from),
+            _ => None,
+        }
     }
 
     fn get_reminder_date(&self) -> Result<String> {
@@ -86,9 +114,9 @@ impl Reminder {
         };
        
         let rem_date = format!("{} {} {}", 
-            if &reminder.day != "" { &reminder.day } else { "" },
+            if !reminder.day.is_empty() { &reminder.day } else { "" },
             month_string,
-            if &reminder.year != "" { &reminder.year } else { "" }
+            if !reminder.year.is_empty() { &reminder.year } else { "" }
         );
 
         Ok(rem_date)
@@ -0,0 +1,472 @@
+extern crate serde_json;
+extern crate tiny_http;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::io::{Error, ErrorKind, Read, Result};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::journal::Journal;
+use crate::layout::Location;
+use crate::task::Task;
+use crate::task_list::{self, TaskList};
+use crate::util::link::decode;
+
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+// The server places new tasks, so these are never taken from a request
+const SERVER_FIELDS: [&str; 4] = ["id", "path", "created", "updated"];
+
+#[derive(Deserialize)]
+struct TaskChanges {
+    #[serde(default)]
+    task_name: Option<String>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct ListEntry {
+    task: String,
+}
+
+#[derive(Deserialize, Default)]
+struct ReviewRequest {
+    #[serde(default)]
+    project: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ListSummary {
+    name: String,
+    count: usize,
+}
+
+struct ApiResponse {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl ApiResponse {
+    fn new(status: u16, body: serde_json::Value) -> ApiResponse {
+        ApiResponse { status, body }
+    }
+
+    fn error(status: u16, message: &str) -> ApiResponse {
+        ApiResponse::new(status, json!({ "error": message }))
+    }
+}
+
+pub fn serve(bind: &str, token: &str) -> Result<()> {
+    if token.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "api-token must be set before serving"));
+    }
+
+    let server = Server::http(bind)
+        .map_err(|e| Error::new(ErrorKind::AddrNotAvailable, e.to_string()))?;
+
+    println!("listening on {}", bind);
+
+    for mut request in server.incoming_requests() {
+        let response = if !is_authorized(&request, token) {
+            ApiResponse::error(401, "unauthorized")
+        } else if request.body_length().is_some_and(|l| l > MAX_BODY_SIZE) {
+            ApiResponse::error(413, "request body is too large")
+        } else {
+            let result = read_body(&mut request)
+                .and_then(|body| handle(request.method(), request.url(), &body));
+            to_response(result)
+        };
+
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("invalid header");
+        let http_response = Response::from_string(response.body.to_string())
+            .with_status_code(response.status)
+            .with_header(content_type);
+
+        if let Err(e) = request.respond(http_response) {
+            eprintln!("could not send response with error {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+fn is_authorized(request: &Request, token: &str) -> bool {
+    let expected = format!("Bearer {}", token);
+    request.headers()
+        .iter()
+        .any(|h| h.field.equiv("Authorization") && is_same_token(h.value.as_str().as_bytes(), expected.as_bytes()))
+}
+
+// Compares every byte, so the time taken does not show how much of a
+// guessed token was right
+fn is_same_token(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given.iter().zip(expected).fold(0, |diff, (g, e)| diff | (g ^ e)) == 0
+}
+
+fn to_response(result: Result<ApiResponse>) -> ApiResponse {
+    match result {
+        Ok(r) => r,
+        Err(e) if e.kind() == ErrorKind::NotFound => ApiResponse::error(404, &e.to_string()),
+        Err(e) if e.kind() == ErrorKind::InvalidInput => ApiResponse::error(400, &e.to_string()),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => ApiResponse::error(409, &e.to_string()),
+        Err(e) => ApiResponse::error(500, &e.to_string()),
+    }
+}
+
+fn handle(method: &Method, url: &str, body: &str) -> Result<ApiResponse> {
+    let (path, query) = match url.find('?') {
+        Some(i) => (&url[..i], &url[i + 1..]),
+        None => (url, ""),
+    };
+
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(|s| s.as_str()).collect();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["tasks"]) => {
+            let project = get_query_value(query, "project").unwrap_or_default();
+            let new_only = get_query_value(query, "new").map(|v| v == "true").unwrap_or(false);
+            let tasks = Task::get_all(new_only, &project)?;
+            Ok(ApiResponse::new(200, to_json(&tasks)?))
+        }
+        (Method::Post, ["tasks"]) => {
+            let fields: serde_json::Map<String, serde_json::Value> = parse_body(body)?;
+            let description = fields.get("description").and_then(|d| d.as_str()).unwrap_or_default().to_string();
+            let task = get_new_task(fields)?;
+            task.add(&description)?;
+            Ok(ApiResponse::new(201, to_json(&task)?))
+        }
+        (Method::Get, ["tasks", task]) => {
+            let task = Task::get_by_id_or_name(task, false, "")?;
+            Ok(ApiResponse::new(200, to_json(&task)?))
+        }
+        (Method::Patch, ["tasks", task]) => {
+            let changes: TaskChanges = parse_body(body)?;
+            let task = update_task(task, changes)?;
+            Ok(ApiResponse::new(200, to_json(&task)?))
+        }
+        (Method::Post, ["tasks", task, "finish"]) => {
+            let task = Task::get_by_id_or_name(task, false, "")?;
+            task.finish("Task Completed")?;
+            Ok(ApiResponse::new(200, to_json(&Task::get_by_id_or_name(&task.id, false, "")?)?))
+        }
+        (Method::Post, ["tasks", task, "cancel"]) => {
+            let task = Task::get_by_id_or_name(task, false, "")?;
+            task.finish("Task Canceled")?;
+            Ok(ApiResponse::new(200, to_json(&Task::get_by_id_or_name(&task.id, false, "")?)?))
+        }
+        (Method::Get, ["lists"]) => {
+            let mut lists: Vec<ListSummary> = Vec::new();
+            for name in task_list::get_names() {
+                let count = TaskList::get(&name).get_tasks()?.len();
+                lists.push(ListSummary { name, count });
+            }
+
+            Ok(ApiResponse::new(200, to_json(&lists)?))
+        }
+        (Method::Get, ["lists", list]) => {
+            let tasks = TaskList::get(list).get_tasks()?;
+            Ok(ApiResponse::new(200, to_json(&tasks)?))
+        }
+        (Method::Post, ["lists", list]) => {
+            let entry: ListEntry = parse_body(body)?;
+            let task = Task::get_by_id_or_name(&entry.task, false, "")?;
+            TaskList::get(list).add(task.clone())?;
+            Ok(ApiResponse::new(200, to_json(&task)?))
+        }
+        (Method::Get, ["journal"]) => {
+            let journal = Journal::new("Journal", "My Thoughts Today")?;
+            let contents = fs::read_to_string(&journal.journal_path)?;
+            Ok(ApiResponse::new(200, json!({ "path": journal.journal_path, "contents": contents })))
+        }
+        (Method::Post, ["journal"]) => {
+            let journal = Journal::new("Journal", "My Thoughts Today")?;
+            let created = journal.create()?;
+            if created {
+                let tasks = TaskList::get(task_list::TODAY_LIST).get_tasks()?;
+                journal.add_tasks_to_journal(tasks);
+            }
+
+            Ok(ApiResponse::new(if created { 201 } else { 200 }, json!({ "path": journal.journal_path })))
+        }
+        (Method::Post, ["review"]) => {
+            let review: ReviewRequest = parse_body(body).unwrap_or_default();
+            let tasks = Task::get_all(true, &review.project.unwrap_or_default())?;
+            let count = tasks.len();
+            Task::create_review(tasks)?;
+            Ok(ApiResponse::new(201, json!({ "tasks": count })))
+        }
+        _ => Ok(ApiResponse::error(404, "no such endpoint")),
+    }
+}
+
+// New tasks take the shape tasks are returned in, with any field left
+// out filled in the way `create` fills it
+fn get_new_task(fields: serde_json::Map<String, serde_json::Value>) -> Result<Task> {
+    let task_name = match fields.get("task_name").and_then(|n| n.as_str()) {
+        Some(n) if !n.trim().is_empty() => n.to_string(),
+        _ => return Err(Error::new(ErrorKind::InvalidInput, "task_name is required")),
+    };
+
+    let project = fields.get("project").and_then(|p| p.as_str()).unwrap_or("Inbox").to_string();
+    let year = crate::setting::get_year().unwrap_or_else(|| Local::now().year());
+
+    let mut task = match to_json(&Task::new(&task_name, &project, &year))? {
+        serde_json::Value::Object(t) => t,
+        _ => return Err(Error::new(ErrorKind::InvalidData, "could not build task")),
+    };
+
+    for (key, value) in fields {
+        if !SERVER_FIELDS.contains(&key.as_str()) {
+            task.insert(key, value);
+        }
+    }
+
+    serde_json::from_value(serde_json::Value::Object(task))
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e.to_string()))
+}
+
+fn update_task(task: &str, changes: TaskChanges) -> Result<Task> {
+    let task = Task::get_by_id_or_name(task, false, "")?;
+    let id = task.id.to_string();
+
+    // Every change is checked before any is made, so one that can not be
+    // made does not leave the task partly changed
+    let project = match &changes.project {
+        Some(p) => crate::project::normalize_name(p)?,
+        None => task.project.to_string(),
+    };
+
+    let task_name = match &changes.task_name {
+        Some(n) if n.trim().is_empty() => {
+            return Err(Error::new(ErrorKind::InvalidInput, "task_name can not be empty"));
+        }
+        Some(n) => n.to_string(),
+        None => task.task_name.to_string(),
+    };
+
+    // The project is changed first, so the task passes through there
+    let current_path = task.get_file_path();
+    let moved = Location { project: project.to_string(), ..task.get_location() };
+    for location in &[moved.clone(), moved.with_name(&task_name)] {
+        for path in &[location.with_status(true).get_file_path(), location.with_status(false).get_file_path()] {
+            if *path != current_path && crate::storage::exists(path) {
+                return Err(Error::new(ErrorKind::AlreadyExists, format!("a task file already exists at {}", path)));
+            }
+        }
+    }
+
+    if project != task.project {
+        task.change_project(&project)?;
+    }
+
+    if task_name != task.task_name {
+        Task::get_by_id_or_name(&id, false, "")?.rename_task(&task_name)?;
+    }
+
+    if changes.priority.is_some() || changes.tags.is_some() {
+        let mut task = Task::get_by_id_or_name(&id, false, "")?;
+        if changes.priority.is_some() {
+            task.priority = changes.priority;
+        }
+
+        if changes.tags.is_some() {
+            task.tags = changes.tags;
+        }
+
+        task.save()?;
+    }
+
+    Task::get_by_id_or_name(&id, false, "")
+}
+
+// Only requests that send a body are read, up to the size limit
+fn read_body(request: &mut Request) -> Result<String> {
+    let mut body = String::new();
+    if *request.method() != Method::Post && *request.method() != Method::Patch {
+        return Ok(body);
+    }
+
+    request.as_reader().take(MAX_BODY_SIZE as u64 + 1).read_to_string(&mut body)?;
+    if body.len() > MAX_BODY_SIZE {
+        return Err(Error::new(ErrorKind::InvalidInput, "request body is too large"));
+    }
+
+    Ok(body)
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T> {
+    serde_json::from_str(body)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e.to_string()))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<serde_json::Value> {
+    serde_json::to_value(value)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))
+}
+
+fn get_query_value(query: &str, key: &str) -> Option<String> {
+    query.split('&')
+        .filter_map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            Some((parts.next()?, parts.next().unwrap_or("")))
+        })
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| decode(&v.replace('+', " ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+
+    fn request(method: Method, url: &str, body: &str) -> (u16, serde_json::Value) {
+        let response = to_response(handle(&method, url, body));
+        (response.status, response.body)
+    }
+
+    #[test]
+    fn compares_tokens() {
+        assert!(is_same_token(b"Bearer secret", b"Bearer secret"));
+        assert!(!is_same_token(b"Bearer secreT", b"Bearer secret"));
+        assert!(!is_same_token(b"Bearer secret2", b"Bearer secret"));
+        assert!(!is_same_token(b"Bearer", b"Bearer secret"));
+        assert!(!is_same_token(b"", b"Bearer secret"));
+    }
+
+    #[test]
+    fn reads_query_values() {
+        let query = "project=Home+Repair&new=true&empty=&name=50%25%20off";
+        assert_eq!(get_query_value(query, "project"), Some("Home Repair".to_string()));
+        assert_eq!(get_query_value(query, "new"), Some("true".to_string()));
+        assert_eq!(get_query_value(query, "empty"), Some("".to_string()));
+        assert_eq!(get_query_value(query, "name"), Some("50% off".to_string()));
+        assert_eq!(get_query_value(query, "missing"), None);
+        assert_eq!(get_query_value("", "project"), None);
+    }
+
+    #[test]
+    fn builds_new_tasks() {
+        let _root = Root::new();
+        let fields = json!({
+            "task_name": "Fix the fence",
+            "project": "Home",
+            "priority": "A",
+            "tags": ["outside"],
+            "id": "chosen-id",
+            "path": "Projects/elsewhere.md",
+            "description": "https://example.com",
+        });
+
+        let task = get_new_task(fields.as_object().unwrap().clone()).unwrap();
+        assert_eq!(task.task_name, "Fix the fence");
+        assert_eq!(task.project, "Home");
+        assert_eq!(task.priority, Some("A".to_string()));
+        assert_eq!(task.tags, Some(vec!["outside".to_string()]));
+        assert_ne!(task.id, "chosen-id");
+        assert!(task.path.ends_with("/Home/Fix the fence.md"));
+
+        let inbox = get_new_task(json!({ "task_name": "Call Bob" }).as_object().unwrap().clone()).unwrap();
+        assert_eq!(inbox.project, "Inbox");
+    }
+
+    #[test]
+    fn rejects_bad_new_tasks() {
+        let _root = Root::new();
+        for fields in &[json!({}), json!({ "task_name": " " }), json!({ "task_name": "Call Bob", "tags": "one" })] {
+            let error = get_new_task(fields.as_object().unwrap().clone()).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn routes_requests() {
+        let _root = Root::new();
+
+        let (status, task) = request(Method::Post, "/tasks", r#"{"task_name": "Call Bob", "project": "Home"}"#);
+        assert_eq!(status, 201);
+        let id = task["id"].as_str().unwrap().to_string();
+
+        assert_eq!(request(Method::Get, "/tasks/Call%20Bob", "").1["id"], id.as_str());
+        assert_eq!(request(Method::Get, "/tasks?project=Home&new=true", "").1.as_array().unwrap().len(), 1);
+        assert_eq!(request(Method::Get, "/tasks?project=Work", "").1.as_array().unwrap().len(), 0);
+
+        assert_eq!(request(Method::Post, "/lists/Today", &format!(r#"{{"task": "{}"}}"#, id)).0, 200);
+        let (status, lists) = request(Method::Get, "/lists", "");
+        assert_eq!(status, 200);
+        assert_eq!(lists[0], json!({ "name": "Today", "count": 1 }));
+        assert_eq!(request(Method::Get, "/lists/Today", "").1[0]["id"], id.as_str());
+
+        let (status, task) = request(Method::Post, &format!("/tasks/{}/finish", id), "");
+        assert_eq!(status, 200);
+        assert_eq!(task["id"], id.as_str());
+        assert_eq!(request(Method::Get, "/lists/Today", "").1, json!([]));
+    }
+
+    #[test]
+    fn reports_errors() {
+        let _root = Root::new();
+
+        assert_eq!(request(Method::Get, "/nothing", "").0, 404);
+        assert_eq!(request(Method::Delete, "/tasks", "").0, 404);
+        assert_eq!(request(Method::Get, "/tasks/Nobody", "").0, 404);
+        assert_eq!(request(Method::Get, "/lists/Later", "").0, 404);
+        assert_eq!(request(Method::Post, "/tasks", "not json").0, 400);
+        assert_eq!(request(Method::Post, "/tasks", r#"{"task_name": "Call Bob", "project": "../x"}"#).0, 400);
+
+        assert_eq!(request(Method::Post, "/tasks", r#"{"task_name": "Call Bob"}"#).0, 201);
+        assert_eq!(request(Method::Post, "/tasks", r#"{"task_name": "Call Bob"}"#).0, 409);
+    }
+
+    #[test]
+    fn patches_listed_tasks() {
+        let root = Root::new();
+        let task = root.add_task("Call Bob", "Home");
+        TaskList::get("Today").add(task.clone()).unwrap();
+
+        let body = r#"{"task_name": "Call Robert", "project": "Work/", "priority": "B"}"#;
+        let (status, patched) = request(Method::Patch, &format!("/tasks/{}", task.id), body);
+        assert_eq!(status, 200, "{}", patched);
+        assert_eq!(patched["task_name"], "Call Robert");
+        assert_eq!(patched["project"], "Work");
+        assert_eq!(patched["priority"], "B");
+        assert!(root.exists("Projects/2026/Work/Call Robert.md"));
+
+        let today = request(Method::Get, "/lists/Today", "").1;
+        assert_eq!(today[0]["task_name"], "Call Robert");
+    }
+
+    #[test]
+    fn patches_nothing_when_a_change_fails() {
+        let root = Root::new();
+        let task = root.add_task("Call Bob", "Home");
+        root.add_task("Call Robert", "Work");
+
+        let body = r#"{"task_name": "Call Robert", "project": "Work", "priority": "B"}"#;
+        assert_eq!(request(Method::Patch, &format!("/tasks/{}", task.id), body).0, 409);
+
+        let body = r#"{"task_name": " ", "priority": "B"}"#;
+        assert_eq!(request(Method::Patch, &format!("/tasks/{}", task.id), body).0, 400);
+
+        let body = r#"{"project": "..", "priority": "B"}"#;
+        assert_eq!(request(Method::Patch, &format!("/tasks/{}", task.id), body).0, 400);
+
+        let unchanged = Task::get_by_id_or_name(&task.id, false, "").unwrap();
+        assert_eq!(unchanged.task_name, "Call Bob");
+        assert_eq!(unchanged.project, "Home");
+        assert_eq!(unchanged.priority, None);
+    }
+}
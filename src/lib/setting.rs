@@ -20,6 +20,22 @@ pub fn get_review_folder() -> String {
     env::var("TODUIT_REVIEW_FOLDER").expect("review folder variable not set")
 }
 
+pub fn get_journal_folder_name() -> String {
+    env::var("TODUIT_JOURNAL_FOLDER_NAME").expect("journal folder name variable not set")
+}
+
+pub fn get_review_folder_name() -> String {
+    env::var("TODUIT_REVIEW_FOLDER_NAME").expect("review folder name variable not set")
+}
+
+pub fn get_archive_folder_name() -> String {
+    env::var("TODUIT_ARCHIVE_FOLDER_NAME").unwrap_or_else(|_| "Archive".to_string())
+}
+
+pub fn get_archive_folder() -> String {
+    format!("{}/{}", get_root_folder(), get_archive_folder_name())
+}
+
 pub fn get_todo_list() -> String {
     env::var("TODUIT_TODO_LISTS").expect("todo lists variable not set")
 }
@@ -27,3 +43,34 @@ pub fn get_todo_list() -> String {
 pub fn get_reminder_file() -> String {
     env::var("TODUIT_REMINDER_FILE").expect("reminder file variable not set")
 }
+
+pub fn get_list_limits() -> Vec<(String, usize)> {
+    env::var("TODUIT_LIST_LIMITS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|l| {
+            let (list, limit) = l.rsplit_once('=')?;
+            Some((list.to_string(), limit.parse::<usize>().ok()?))
+        })
+        .collect()
+}
+
+pub fn get_list_limit(list: &str) -> Option<usize> {
+    get_list_limits().into_iter().find(|(l, _n)| l == list).map(|(_l, n)| n)
+}
+
+pub fn get_api_token() -> String {
+    env::var("TODUIT_API_TOKEN").unwrap_or_default()
+}
+
+pub fn get_year() -> Option<i32> {
+    env::var("TODUIT_YEAR").ok().and_then(|y| y.parse::<i32>().ok())
+}
+
+pub fn is_dry_run() -> bool {
+    env::var("TODUIT_DRY_RUN").is_ok()
+}
+
+pub fn get_path_template() -> String {
+    env::var("TODUIT_PATH_TEMPLATE").unwrap_or_else(|_| crate::layout::DEFAULT_TEMPLATE.to_string())
+}
@@ -0,0 +1,211 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Result, Write};
+use std::path::{Path, PathBuf};
+
+// Every change to the task store goes through these functions. In dry run
+// mode the change is printed instead, and remembered so that later reads in
+// the same command see the planned state rather than the files on disk
+#[derive(Default)]
+struct Overlay {
+    files: HashMap<PathBuf, Option<String>>,
+    folders: HashSet<PathBuf>,
+}
+
+thread_local! {
+    static OVERLAY: RefCell<Overlay> = RefCell::new(Overlay::default());
+}
+
+fn is_dry_run() -> bool {
+    crate::setting::is_dry_run()
+}
+
+pub fn exists<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    if is_dry_run() {
+        let planned = OVERLAY.with(|o| {
+            let overlay = o.borrow();
+            if overlay.folders.contains(path) {
+                return Some(true);
+            }
+
+            overlay.files.get(path).map(|f| f.is_some())
+        });
+
+        if let Some(p) = planned {
+            return p;
+        }
+    }
+
+    path.exists()
+}
+
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    if is_dry_run() {
+        let planned = OVERLAY.with(|o| o.borrow().files.get(path).cloned());
+        match planned {
+            Some(Some(contents)) => return Ok(contents),
+            Some(None) => return Err(Error::new(ErrorKind::NotFound, "file not found")),
+            None => (),
+        }
+    }
+
+    fs::read_to_string(path)
+}
+
+pub fn write<P: AsRef<Path>>(path: P, contents: &str) -> Result<()> {
+    let path = path.as_ref();
+    if is_dry_run() {
+        let action = if exists(path) { "write" } else { "create" };
+        println!("would {} {}", action, path.display());
+        set_planned(path, Some(contents.to_string()));
+        return Ok(());
+    }
+
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_data()
+}
+
+pub fn append<P: AsRef<Path>>(path: P, contents: &str) -> Result<()> {
+    let path = path.as_ref();
+    if is_dry_run() {
+        let existing = read_to_string(path)?;
+        for line in contents.lines() {
+            println!("would append to {}: {}", path.display(), line.trim_end());
+        }
+
+        set_planned(path, Some(existing + contents));
+        return Ok(());
+    }
+
+    let mut file = OpenOptions::new().append(true).open(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_data()
+}
+
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    if is_dry_run() {
+        let contents = read_to_string(from)?;
+        println!("would move {} to {}", from.display(), to.display());
+        set_planned(from, None);
+        set_planned(to, Some(contents));
+        return Ok(());
+    }
+
+    fs::rename(from, to)
+}
+
+pub fn remove_file<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    if is_dry_run() {
+        if !exists(path) {
+            return Err(Error::new(ErrorKind::NotFound, "file not found"));
+        }
+
+        println!("would delete {}", path.display());
+        set_planned(path, None);
+        return Ok(());
+    }
+
+    fs::remove_file(path)
+}
+
+pub fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    if is_dry_run() {
+        if !exists(path) {
+            println!("would create folder {}", path.display());
+            OVERLAY.with(|o| o.borrow_mut().folders.insert(path.to_path_buf()));
+        }
+
+        return Ok(());
+    }
+
+    fs::create_dir_all(path)
+}
+
+// Only removes empty folders, so entries are moved or deleted first
+pub fn remove_dir<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    if is_dry_run() {
+        println!("would delete folder {}", path.display());
+        OVERLAY.with(|o| o.borrow_mut().folders.remove(path));
+        return Ok(());
+    }
+
+    fs::remove_dir(path)
+}
+
+fn set_planned(path: &Path, contents: Option<String>) {
+    OVERLAY.with(|o| o.borrow_mut().files.insert(path.to_path_buf(), contents));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+
+    #[test]
+    fn plans_changes_without_touching_disk() {
+        let root = Root::new();
+        let path = Path::new(&root.path);
+        fs::write(path.join("real.md"), "on disk\n").unwrap();
+        std::env::set_var("TODUIT_DRY_RUN", "1");
+
+        write(path.join("new.md"), "planned\n").unwrap();
+        append(path.join("new.md"), "more\n").unwrap();
+        append(path.join("real.md"), "appended\n").unwrap();
+        create_dir_all(path.join("Later")).unwrap();
+
+        assert_eq!(read_to_string(path.join("new.md")).unwrap(), "planned\nmore\n");
+        assert_eq!(read_to_string(path.join("real.md")).unwrap(), "on disk\nappended\n");
+        assert!(exists(path.join("Later")));
+
+        assert!(!path.join("new.md").exists());
+        assert!(!path.join("Later").exists());
+        assert_eq!(fs::read_to_string(path.join("real.md")).unwrap(), "on disk\n");
+    }
+
+    #[test]
+    fn hides_moved_and_deleted_files() {
+        let root = Root::new();
+        let path = Path::new(&root.path);
+        fs::write(path.join("moved.md"), "moved\n").unwrap();
+        fs::write(path.join("deleted.md"), "deleted\n").unwrap();
+        std::env::set_var("TODUIT_DRY_RUN", "1");
+
+        rename(path.join("moved.md"), path.join("Today/moved.md")).unwrap();
+        remove_file(path.join("deleted.md")).unwrap();
+
+        assert!(!exists(path.join("moved.md")));
+        assert_eq!(read_to_string(path.join("Today/moved.md")).unwrap(), "moved\n");
+        assert!(!exists(path.join("deleted.md")));
+        assert_eq!(read_to_string(path.join("deleted.md")).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(remove_file(path.join("deleted.md")).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(rename(path.join("moved.md"), path.join("again.md")).unwrap_err().kind(), ErrorKind::NotFound);
+
+        assert!(path.join("moved.md").exists());
+        assert!(path.join("deleted.md").exists());
+        assert!(!path.join("Today/moved.md").exists());
+    }
+
+    #[test]
+    fn writes_to_disk_without_dry_run() {
+        let root = Root::new();
+        let path = Path::new(&root.path);
+
+        write(path.join("new.md"), "one\n").unwrap();
+        append(path.join("new.md"), "two\n").unwrap();
+        rename(path.join("new.md"), path.join("Today/new.md")).unwrap();
+
+        assert!(!path.join("new.md").exists());
+        assert_eq!(fs::read_to_string(path.join("Today/new.md")).unwrap(), "one\ntwo\n");
+        remove_file(path.join("Today/new.md")).unwrap();
+        assert!(!exists(path.join("Today/new.md")));
+    }
+}
@@ -5,20 +5,20 @@ extern crate serde_yaml;
 use chrono::prelude::*;
 use chrono::DateTime;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::fs::{File, OpenOptions};
 use std::io::{Error, ErrorKind};
-use std::io::prelude::*;
-use std::io::BufReader;
 use std::io::Result;
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use uuid::Uuid;
-use walkdir::WalkDir;
 
+use crate::fuzzy;
+use crate::layout;
+use crate::layout::Location;
+use crate::storage;
 use crate::util::date_format;
+use crate::util::optional_date;
 use crate::task_list;
 use crate::reminder::Reminder;
+use crate::util::link;
 
 #[derive(Debug)]
 #[derive(Clone, Serialize, Deserialize)]
@@ -37,18 +37,19 @@ pub struct Task {
     pub exclude_from_logging: Option<bool>,
 
     pub remind: Option<Reminder>,
+
+    pub priority: Option<String>,
+    pub tags: Option<Vec<String>>,
+
+    pub waiting_on: Option<String>,
+    #[serde(default, with = "optional_date")]
+    pub follow_up: Option<NaiveDate>,
 }
 
 impl Task {
     pub fn new(task_name: &str, project: &str, year: &i32) -> Task {
         let created = Local::now();
-        let task_path = format!(
-            "{}/{}/{}/{}.md",
-            crate::setting::get_project_folder_name(),
-            year,
-            project,
-            task_name
-        );
+        let task_path = Location::new(task_name, project, *year, false).get_relative_path();
 
         Task {
             id: Uuid::new_v4().to_string(),
@@ -59,19 +60,25 @@ impl Task {
             updated: created,
             exclude_from_journal: None,
             exclude_from_logging: Some(false),
-            remind: None
+            remind: None,
+            priority: None,
+            tags: None,
+            waiting_on: None,
+            follow_up: None,
         }
     }
 
     pub fn get(filepath: &str) -> Result<Task> {
-        let p = PathBuf::from(filepath);
-        let newfile = File::open(p).expect("could not open file");
-
-        let mut buf_reader = BufReader::new(newfile);
-        let mut contents = String::new();
-        buf_reader.read_to_string(&mut contents)?;
-        let contents = contents.split("---");
-        let fm = contents.collect::<Vec<&str>>();
+        let contents = storage::read_to_string(filepath)?;
+        Task::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Task> {
+        let fm = contents.split("---").collect::<Vec<&str>>();
+        if fm.len() < 3 {
+            return Err(Error::new(ErrorKind::InvalidData, "missing frontmatter"));
+        }
+
         let mut ymltask = fm[1].to_string();
         if ymltask.find("id:").is_none() {
             ymltask.push_str("id: ");
@@ -92,171 +99,273 @@ impl Task {
              ymltask.push_str(&format!("updated: \"{}\"", todaystr));
         }
 
-        let task: Task = serde_yaml::from_str(&ymltask).expect("could not deserialize");
-
-        Ok(task)
+        serde_yaml::from_str(&ymltask)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("could not deserialize: {}", e)))
     }
 
     pub fn get_by_id_or_name(task: &str, new_only: bool, project: &str) -> Result<Task> {
-        let project_folder = format!(
-            "{}/{}",
-            crate::setting::get_project_folder(),
-            if project != "" { project } else { "" }
-        );
+        for f_path in get_task_files(new_only, project) {
+            let f_task = Task::get(&f_path)?;
+            if f_task.id == task || f_task.task_name == task {
+                return Ok(f_task);
+            }
+        }
 
-        for entry in WalkDir::new(project_folder)
-            .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok()) {
-                    let f_path = entry.path().to_str().unwrap();
-
-                    if f_path.ends_with(".md") {
-                        if new_only && !f_path.contains("/new/") {
-                            continue;
-                        }
-
-                        let f_task = Task::get(f_path)?;
-                        if f_task.id == task || f_task.task_name == task {
-                            return Ok(f_task);
-                        }
-                    }
+        Err(Error::new(ErrorKind::NotFound, "not found"))
+    }
+
+    // Loads a task from the path stored in its frontmatter or a list link,
+    // wherever its status puts the file
+    pub fn get_by_path(task_path: &str) -> Result<Task> {
+        let location = Location::from_task_path(task_path)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "path does not match the path template"))?;
+
+        for file_path in &[location.with_status(false).get_file_path(), location.with_status(true).get_file_path()] {
+            if storage::exists(file_path) {
+                return Task::get(file_path);
+            }
         }
 
         Err(Error::new(ErrorKind::NotFound, "not found"))
     }
 
+    // Returns every task whose id or name matches the query, best first
+    pub fn get_matches(task: &str, new_only: bool, project: &str) -> Result<Vec<(i64, Task)>> {
+        let mut matches: Vec<(i64, Task)> = Task::get_all(new_only, project)?
+            .into_iter()
+            .filter_map(|t| {
+                let score = if t.id == task { Some(fuzzy::CLEAR_MATCH + 1) } else { fuzzy::score(task, &t.task_name) };
+                score.map(|s| (s, t))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.task_name.cmp(&b.1.task_name)));
+
+        Ok(matches)
+    }
+
+    // Queries are whitespace separated terms that must all match, either
+    // `project:`, `tag:` or `list:` filters or text found in the task name
+    pub fn query(query: &str, new_only: bool, project: &str) -> Result<Vec<Task>> {
+        let terms: Vec<&str> = query.split_whitespace().collect();
+
+        Ok(Task::get_all(new_only, project)?
+            .into_iter()
+            .filter(|t| terms.iter().all(|term| t.matches_term(term)))
+            .collect())
+    }
+
+    fn matches_term(&self, term: &str) -> bool {
+        if let Some(project) = term.strip_prefix("project:") {
+            return crate::project::matches(&project.to_lowercase(), &self.project.to_lowercase());
+        }
+
+        if let Some(tag) = term.strip_prefix("tag:") {
+            return match &self.tags {
+                Some(tags) => tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+                None => false,
+            };
+        }
+
+        if let Some(list) = term.strip_prefix("list:") {
+            return task_list::get_lists_for_task(&self.task_name)
+                .iter()
+                .any(|l| l.eq_ignore_ascii_case(list));
+        }
+
+        self.task_name.to_lowercase().contains(&term.to_lowercase())
+    }
+
     pub fn get_all(new_only: bool, project: &str) -> Result<Vec<Task>> {
         let mut task_list: Vec<Task> = Vec::new();
-        let project_folder = format!(
-            "{}/{}",
-            crate::setting::get_project_folder(),
-            if project != "" { project } else { "" }
-        );
-            
-        for entry in WalkDir::new(&project_folder)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok()) {
-                let f_path = entry.path().to_str().unwrap();
-                if f_path.ends_with(".md") {
-                    if new_only && !f_path.contains("/new/") {
-                        continue;
-                    }
-                    
-                    match Task::get(f_path) {
-                        Ok(v) => task_list.push(v),
-                        Err(e) => eprintln!("could not find task {} with error {}", f_path, e),
-                    }
-                }
+        for f_path in get_task_files(new_only, project) {
+            match Task::get(&f_path) {
+                Ok(v) => task_list.push(v),
+                Err(e) => eprintln!("could not find task {} with error {}", f_path, e),
             }
+        }
 
         Ok(task_list)
     }
 
     pub fn is_excluded(&self) -> bool {
-        match &self.exclude_from_journal {
-            Some(x) => if *x { return true },
-            None => ()
-        };
+        if self.exclude_from_journal == Some(true) {
+            return true;
+        }
 
-        match &self.exclude_from_logging {
-            Some(x) => if *x { return true },
-            None => ()
-        };
+        if self.exclude_from_logging == Some(true) {
+            return true;
+        }
 
         false
     }
 
-    pub fn year_turnover(old_year: &str, new_year: &str) -> Result<()> {
-        let root_folder = crate::setting::get_root_folder();
-        let project_root_folder = format!("{}/Projects/{}", root_folder, old_year);
-        for entry in WalkDir::new(project_root_folder)
-            .follow_links(true)
+    // Moves the open tasks filed under `old_year` to `new_year`, changing only
+    // the year folder. Returns what happened to each file, so one bad file
+    // does not stop the turnover
+    pub fn year_turnover(old_year: i32, new_year: i32) -> Result<Vec<(String, Result<Task>)>> {
+        if !layout::has_year(&layout::get_template()) {
+            return Err(Error::new(ErrorKind::InvalidInput, "the path template has no {year} folder to turn over"));
+        }
+
+        Ok(Task::get_open_files(old_year)
+            .into_iter()
+            .map(|(f_path, location)| {
+                let new_location = Location { year: Some(new_year), ..location };
+                let task = Task::move_to_year(&f_path, &new_location);
+                (f_path, task)
+            })
+            .collect())
+    }
+
+    // Open tasks are the new ones and those still on a list
+    pub fn get_open_files(year: i32) -> Vec<(String, Location)> {
+        layout::get_task_files()
             .into_iter()
-            .filter_map(|e| e.ok()) {
-                let f_path = entry.path().to_str().unwrap();
-                if f_path.ends_with(".md") && f_path.contains("/new/") {
-                    let new_task_path = f_path.replace(&old_year, &new_year);
-                    let new_path = Task::get_new_folder(&new_task_path);
-                    if !Path::new(&new_path).exists() {
-                        match fs::create_dir_all(&new_path){
-                            Ok(v) => v,
-                            Err(e) => eprintln!("could not create folder {} with error {}", new_path, e),
-                        }
-                    }
-
-                    // println!("old path: {}\n newpath: {}\n", f_path, &new_task_path);
-                    match fs::rename(f_path, &new_task_path) {
-                        Ok(v) => v,
-                        Err(e) => eprintln!("could not rename {} with error {}", f_path, e),
-                    };
-
-                    let mut f_task = Task::get(&new_task_path)?;
-                    f_task.path = f_task.path.replace(&old_year, &new_year);
-                    f_task.save().expect("could not save task");
+            .filter(|(_f, l)| l.year == Some(year))
+            .filter(|(f, l)| l.is_new || Task::get(f).map(|t| !t.is_finished()).unwrap_or(false))
+            .collect()
+    }
+
+    fn move_to_year(f_path: &str, new_location: &Location) -> Result<Task> {
+        let new_task_path = new_location.get_file_path();
+        if storage::exists(&new_task_path) {
+            return Err(Error::new(ErrorKind::AlreadyExists, format!("{} already exists", new_task_path)));
+        }
+
+        create_parent_folder(&new_task_path)?;
+        storage::rename(f_path, &new_task_path)?;
+
+        let mut task = Task::get(&new_task_path)?;
+        task.path = new_location.with_status(false).get_relative_path();
+
+        let task_link = task_list::get_task_link(&task);
+        for list in task_list::get_lists_for_task(&task.task_name) {
+            storage::write(task_list::get_entry_path(&list, &task.task_name), &task_link)?;
+        }
+
+        task.clone().save()?;
+        Ok(task)
+    }
+
+    // Moves every task laid out by `from_template` to where the configured
+    // path template puts it, returning the old and new file paths
+    pub fn migrate_layout(from_template: &str) -> Result<Vec<(String, String)>> {
+        layout::validate(from_template)?;
+        let mut moved: Vec<(String, String)> = Vec::new();
+
+        for (f_path, location) in layout::get_task_files_with(from_template) {
+            let task = match Task::get(&f_path) {
+                Ok(t) => t,
+                Err(e) => {
+                    eprintln!("could not read {} with error {}", f_path, e);
+                    continue;
                 }
+            };
+
+            let year = location.year.unwrap_or_else(|| task.created.year());
+            let new_location = Location::new(&task.task_name, &task.project, year, location.is_new);
+            let new_path = new_location.get_file_path();
+            if new_path == f_path {
+                continue;
             }
 
-        Ok(())
+            if storage::exists(&new_path) {
+                eprintln!("skipping {}, {} already exists", f_path, new_path);
+                continue;
+            }
 
+            create_parent_folder(&new_path)?;
+            storage::rename(&f_path, &new_path)?;
+
+            let mut moved_task = Task::get(&new_path)?;
+            moved_task.path = new_location.with_status(false).get_relative_path();
+
+            let task_link = task_list::get_task_link(&moved_task);
+            for list in task_list::get_lists_for_task(&moved_task.task_name) {
+                storage::write(task_list::get_entry_path(&list, &moved_task.task_name), &task_link)?;
+            }
+
+            moved_task.save()?;
+            moved.push((f_path, new_path));
+        }
+
+        Ok(moved)
     }
 
     pub fn rename_task(&self, new_name: &str) -> Result<()> {
         let is_new = &self.check_is_new()?;
-        Task::add_comment(&self, &format!("Task renamed. Previous name was {}", self.task_name), *is_new)?;
-        let project_root_folder = crate::setting::get_project_folder();
-        let mut project_folder = self.project.to_string();
-        if *is_new {
-            project_folder += "/new";
+        let old_path = self.get_file_path();
+        let new_location = self.get_location().with_name(new_name);
+        let new_full_path = new_location.with_status(*is_new).get_file_path();
+        for existing in &[new_location.with_status(true).get_file_path(), new_location.with_status(false).get_file_path()] {
+            if *existing != old_path && storage::exists(existing) {
+                return Err(Error::new(ErrorKind::AlreadyExists, format!("a task file already exists at {}", existing)));
+            }
         }
 
-        let old_path = format!("{}/{}/{}.md", project_root_folder, project_folder, &self.task_name);
-        let new_path = &self.path.replace(&self.task_name, new_name);
-        let new_full_path = old_path.replace(&self.task_name, new_name);
-        fs::rename(old_path, &new_full_path).expect("could not rename file");
+        Task::add_comment(self, &format!("Task renamed. Previous name was {}", self.task_name), *is_new)?;
+        storage::rename(old_path, &new_full_path)?;
 
-        let mut task = Task::get_by_id_or_name(&self.task_name, false, "").expect("could not load task").clone();
+        let mut task = Task::get(&new_full_path)?;
         task.task_name = new_name.to_string();
-        task.path = new_path.to_string();
-
-        task.save().expect("could not save renamed task");
+        task.path = new_location.get_relative_path();
 
-        Ok(())
+        task.clone().save()?;
+        task_list::rename_in_lists(&task, &self.task_name)
     }
 
     pub fn change_project(&self, new_project: &str) -> Result<()> {
-        let old_project = &self.project;
-        Task::add_comment(&self, &format!("Project changed to {}", new_project), true)?;
-        let project_root_folder = crate::setting::get_project_folder();
-        let task_path = format!("{}/{}/new/{}.md", project_root_folder, old_project, &self.task_name);
-        
-        if !Path::new(&task_path).exists() {
+        let new_project = &crate::project::normalize_name(new_project)?;
+        let is_new = self.check_is_new()?;
+        let location = self.get_location().with_status(is_new);
+        let task_path = location.get_file_path();
+
+        if !storage::exists(&task_path) {
             return Err(Error::new(ErrorKind::NotFound, "task file not found"));
         }
-    
-        let new_path = task_path.replace(old_project, new_project);
-        let new_folder = &Task::get_new_folder(&new_path);
-        if !Path::new(new_folder).exists() {
-            fs::create_dir_all(&new_folder).expect("could not create folder");
+
+        let new_location = Location { project: new_project.to_string(), ..location };
+        let new_path = new_location.get_file_path();
+        for existing in &[new_location.with_status(true).get_file_path(), new_location.with_status(false).get_file_path()] {
+            if *existing != task_path && storage::exists(existing) {
+                return Err(Error::new(ErrorKind::AlreadyExists, format!("a task file already exists at {}", existing)));
+            }
         }
 
-        fs::rename(task_path, &new_path).expect("could not rename the file"); 
+        Task::add_comment(self, &format!("Project changed to {}", new_project), is_new)?;
+        create_parent_folder(&new_path)?;
+        storage::rename(task_path, &new_path)?;
 
-        let mut task = Task::get(&new_path).expect("could not load task").clone();
+        let mut task = Task::get(&new_path)?;
         task.project = new_project.to_string();
-        task.path = task.path.replace(old_project, new_project);
-        task.save().expect("could not save task");
+        task.path = new_location.with_status(false).get_relative_path();
+        task.clone().save()?;
+
+        // Listed tasks are found through their list links
+        let task_link = task_list::get_task_link(&task);
+        for list in task_list::get_lists_for_task(&task.task_name) {
+            storage::write(task_list::get_entry_path(&list, &task.task_name), &task_link)?;
+        }
 
         Ok(())
     }
 
     pub fn add(&self, description: &str) -> Result<()> {
+        crate::project::validate_name(&self.project)?;
         let ymltask = serde_yaml::to_string(&self).unwrap();
-        let project_folder = crate::setting::get_project_folder();
-        let folder_path = get_taskfolder(&project_folder, &self.project, true).unwrap();
+        let location = self.get_location();
+        let filepath = location.with_status(true).get_file_path();
+
+        // Different names can share a file name once made safe
+        for existing in &[filepath.to_string(), location.with_status(false).get_file_path()] {
+            if storage::exists(existing) {
+                return Err(Error::new(ErrorKind::AlreadyExists, format!("a task file already exists at {}", existing)));
+            }
+        }
+
+        create_parent_folder(&filepath)?;
 
-        let filepath = format!("{}/{}.md", folder_path, &self.task_name);
-        let mut taskfile = File::create(&filepath)?;
         let (is_pm, hour) = self.created.hour12();
         let today = format!(
             "{:02}/{:02}/{:02} {:02}:{:02} {}",
@@ -268,65 +377,51 @@ impl Task {
             if is_pm { "PM" } else { "AM" }
         );
 
-        taskfile.write_all(format!("{} \n---\n", ymltask).as_bytes())?;
-        taskfile.write_all(format!("##### {} \nTask Created\n\n", today).as_bytes())?;
-        if description != "" {
-            taskfile.write_all(format!("\n\n[link]({})", description).as_bytes())?;
+        let mut contents = format!("{} \n---\n##### {} \nTask Created\n\n", ymltask, today);
+        if !description.is_empty() {
+            contents.push_str(&format!("\n\n[link]({})", description));
         }
 
-        taskfile.sync_data()?;
-        Ok(())
+        storage::write(&filepath, &contents)
     }
 
     pub fn save(self) -> Result<()> {
-        let is_new = &self.check_is_new()?;
-        let project_root_folder = crate::setting::get_project_folder();
-        let mut project_folder = self.project.to_string();
-        if *is_new {
-            project_folder += "/new";
-        }
+        self.save_at(Local::now())
+    }
+
+    // Imports keep the time a task was last changed before it came here
+    pub fn save_at(self, updated: DateTime<Local>) -> Result<()> {
+        let file_path = self.get_file_path();
 
         let mut task = self;
-        if task.id == "" || task.id == "~" {
+        if task.id.is_empty() || task.id == "~" {
             task.id = Uuid::new_v4().to_string();
         }
 
-        task.updated = Local::now();
+        task.updated = updated;
         let ymltask = serde_yaml::to_string(&task).unwrap();
-        let file_path = format!("{}/{}/{}.md", project_root_folder, project_folder, task.task_name);
 
-        if !Path::new(&file_path).exists() {
+        if !storage::exists(&file_path) {
             return Err(Error::new(ErrorKind::NotFound, "task file not found"));
         };
 
-        let data = fs::read_to_string(&file_path).expect("unable to read file");
+        let data = storage::read_to_string(&file_path)?;
         let contents: Vec<&str> = data.split("---").collect();
 
-        let mut task_file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(&file_path)
-            .expect("could not open task file");
-        
-        task_file.write_all(format!("{} \n---", ymltask).as_bytes())?;
-        task_file.write_all(contents[2].as_bytes())?;
-        
-        task_file.sync_data()?;
-
-        Ok(())
+        storage::write(&file_path, &format!("{} \n---{}", ymltask, contents[2]))
     }
 
     pub fn finish(&self, comment: &str) -> Result<()> {
-        match Task::add_comment(&self, comment, false) {
-            Ok(_o) => (),
-            Err(_e) => Task::add_comment(&self, comment, true).expect("could not add comment")
+        self.finish_at(comment, Local::now())
+    }
+
+    pub fn finish_at(&self, comment: &str, finished: DateTime<Local>) -> Result<()> {
+        if Task::add_comment_at(self, comment, false, finished).is_err() {
+            Task::add_comment_at(self, comment, true, finished)?;
         }
-        
-        task_list::remove_from_lists(&self.task_name, "none");
-        self.change_task_folder().expect("Could not change task folder");
 
-        Ok(())
+        task_list::remove_from_lists(self, "none")?;
+        self.change_task_folder()
     }
 
     pub fn set_reminder(&self, month: &str, day: &str, year: &str, time: &str, notice: &u32) -> Result<()> {
@@ -343,39 +438,64 @@ impl Task {
         task.save()
     }
 
+    pub fn wait(&self, waiting_on: &str, follow_up: Option<NaiveDate>, force: bool) -> Result<()> {
+        let waiting = task_list::TaskList::get(crate::waiting::WAITING_LIST);
+        match force {
+            true => waiting.add_over_limit(self.clone())?,
+            false => waiting.add(self.clone())?,
+        }
+
+        let mut task = Task::get(&self.get_file_path())?;
+        task.waiting_on = Some(waiting_on.to_string());
+        task.follow_up = follow_up;
+        task.clone().save()?;
+
+        let comment = match follow_up {
+            Some(date) => format!("Waiting on {}, follow up on {}", waiting_on, date.format("%m/%d/%Y")),
+            None => format!("Waiting on {}", waiting_on),
+        };
+
+        task.add_comment(&comment, false)
+    }
+
+    pub fn add_tag(&self, tag: &str) -> Result<()> {
+        let mut task = self.clone();
+        let mut tags = task.tags.unwrap_or_default();
+        if tags.iter().any(|t| t == tag) {
+            return Ok(());
+        }
+
+        tags.push(tag.to_string());
+        task.tags = Some(tags);
+        task.save()
+    }
+
+    pub fn remove_tag(&self, tag: &str) -> Result<()> {
+        let mut task = self.clone();
+        let mut tags = task.tags.unwrap_or_default();
+        tags.retain(|t| t != tag);
+
+        task.tags = if tags.is_empty() { None } else { Some(tags) };
+        task.save()
+    }
+
     pub fn add_comment(&self, comment: &str, is_new: bool) -> Result<()> {
+        self.add_comment_at(comment, is_new, Local::now())
+    }
+
+    pub fn add_comment_at(&self, comment: &str, is_new: bool, updated: DateTime<Local>) -> Result<()> {
         if self.is_excluded() {
             return Ok(());
         }
 
-        let file_path = match is_new {
-            false => format!("{}/{}",
-                        crate::setting::get_root_folder(),
-                        &self.path
-                     ),
-            true => format!("{}/{}.md",
-                        Task::get_new_folder(&self.path),
-                        &self.task_name
-                    )
-        };
+        let file_path = self.get_location().with_status(is_new).get_file_path();
         
-        if !Path::new(&file_path).exists() {
+        if !storage::exists(&file_path) {
             return Err(Error::new(ErrorKind::NotFound, "task file not found"));
         };
         
-        let data;
-        match fs::read_to_string(&file_path) {
-            Ok(d) => data = d,
-            Err(e) => return Err(e)
-        }
+        let data = storage::read_to_string(&file_path)?;
 
-        let mut task_file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(&file_path)
-            .expect("could not open task file");
-        
-        let updated = Local::now();
         let (is_pm, hour) = updated.hour12();
         let updated_str = format!(
             "{:02}/{:02}/{:02} {:02}:{:02} {}",
@@ -393,79 +513,98 @@ impl Task {
             .trim_end_matches('\n');
 
 
-        task_file.write_all(format!("---\n{}\n---\n", ymlvalue).as_bytes())?;
-        task_file.write_all(format!("##### {} \n{}\n\n", updated_str, comment).as_bytes())?;
-        task_file.write_all(contents[2].as_bytes())?;
-        task_file.sync_data()?;
+        storage::write(
+            &file_path,
+            &format!("---\n{}\n---\n##### {} \n{}\n\n{}", ymlvalue, updated_str, comment, contents[2])
+        )
+    }
 
-        Ok(())
+    // Finishing logs why the task was finished, so the newest of those
+    // comments says when and how it was finished
+    pub fn get_finish(&self) -> Option<(DateTime<Local>, String)> {
+        self.get_comments()
+            .ok()?
+            .into_iter()
+            .find(|(_d, c)| c == "Task Completed" || c == "Task Canceled")
     }
 
-    pub fn change_task_folder(&self) -> Result<()> {
-        let root_folder = crate::setting::get_root_folder();
-        let mut entries: Vec<&str> = self.path.split_terminator("/").collect();
-        entries.remove(entries.len() -1);
-
-        let file_path = format!(
-            "{}/{}/{}/{}.md",
-            root_folder,
-            entries.join("/"),
-            "/new/",
-            &self.task_name
-        );
+    // Comments are returned in file order, which is newest first
+    pub fn get_comments(&self) -> Result<Vec<(DateTime<Local>, String)>> {
+        let data = storage::read_to_string(self.get_file_path())?;
+        let contents: Vec<&str> = data.splitn(3, "---\n").collect();
+        let body = contents.get(2).unwrap_or(&"");
+        let mut comments: Vec<(DateTime<Local>, String)> = Vec::new();
 
-        let new_path = format!(
-            "{}/{}",
-            root_folder,
-            &self.path
-        );
+        for entry in body.split("##### ").skip(1) {
+            let mut lines = entry.splitn(2, '\n');
+            let header = lines.next().unwrap_or("").trim();
+            let comment = lines.next().unwrap_or("").trim();
+
+            let date = NaiveDateTime::parse_from_str(header, "%m/%d/%Y %I:%M %p")
+                .ok()
+                .and_then(|d| Local.from_local_datetime(&d).single());
 
-        if Path::new(&file_path).exists() {
-            fs::rename(file_path, &new_path).expect("could not rename the file");
+            if let Some(d) = date {
+                comments.push((d, comment.to_string()));
+            }
+        }
+
+        Ok(comments)
+    }
+
+    pub fn get_file_path(&self) -> String {
+        let is_new = self.check_is_new().unwrap_or(false);
+        self.get_location().with_status(is_new).get_file_path()
+    }
+
+    // Tasks whose path does not match the path template are placed where
+    // a new task with the same name and project would be
+    pub fn get_location(&self) -> Location {
+        Location::from_task_path(&self.path)
+            .unwrap_or_else(|| Location::new(&self.task_name, &self.project, self.created.year(), false))
+    }
+
+    pub fn change_task_folder(&self) -> Result<()> {
+        let location = self.get_location();
+        let file_path = location.with_status(true).get_file_path();
+        let new_path = location.with_status(false).get_file_path();
+
+        if storage::exists(&file_path) {
+            create_parent_folder(&new_path)?;
+            storage::rename(file_path, &new_path)?;
         };
 
         Ok(())
     }
 
-    pub fn move_to_new_folder(&self) {
-        let project_folder = crate::setting::get_project_folder();
-        let newpath = format!(
-            "{}/{}/new/{}.md",
-            project_folder,
-            self.project,
-            self.task_name
-        );
+    pub fn unlist(&self) -> Result<()> {
+        Task::add_comment(self, "Unlisted", false)?;
+        self.move_to_new_folder()?;
+        task_list::remove_from_lists(self, "")
+    }
 
-        let oldpath = format!(
-            "{}/{}/{}.md",
-            project_folder,
-            self.project,
-            self.task_name
-        );
+    pub fn move_to_new_folder(&self) -> Result<()> {
+        let location = self.get_location();
+        let newpath = location.with_status(true).get_file_path();
+        let oldpath = location.with_status(false).get_file_path();
 
-        if Path::new(&oldpath).exists() {
-            fs::rename(oldpath, &newpath).expect("could not rename the file");
+        if storage::exists(&oldpath) {
+            create_parent_folder(&newpath)?;
+            storage::rename(oldpath, &newpath)?;
         }
-    }
-
-    fn get_new_folder(task_path: &str) -> String {
-        let root_folder = format!("{}/", &crate::setting::get_root_folder());
-        let ftask_path = &task_path.replace(&root_folder, "");
-        let mut entries: Vec<&str> = ftask_path.split_terminator("/").collect();
-        if ftask_path.contains("/new/") {
-            entries.remove(entries.len() -2);
-        } 
 
+        Ok(())
+    }
 
-        entries.remove(entries.len() -1);
-        let project_path = entries.join("/");
-        format!("{}/{}/new/", crate::setting::get_root_folder(), project_path)
+    // Listed tasks also live outside of the new folder, so a task is only
+    // finished once it has left the new folder and every list
+    pub fn is_finished(&self) -> bool {
+        !self.check_is_new().unwrap_or(true) && task_list::get_lists_for_task(&self.task_name).is_empty()
     }
 
-    fn check_is_new(&self) -> Result<bool> {
-        let mut new_path = Task::get_new_folder(&self.path);
-        new_path = format!("{}{}.md", new_path, &self.task_name);
-        Ok(Path::new(&new_path).exists()) 
+    pub fn check_is_new(&self) -> Result<bool> {
+        let new_path = self.get_location().with_status(true).get_file_path();
+        Ok(storage::exists(&new_path))
     }
 
     pub fn create_review(tasks: Vec<Task>) -> Result<()> {
@@ -479,78 +618,187 @@ impl Task {
             date.year()
         );
     
-        let mut review_file  = File::create(&review_file_path)?;
-        
-        review_file.write_all(
-            format!(
-                "# {:02}/{:02}/{} Review \n\n",
-                date.month(),
-                date.day(),
-                date.year()
-            ).as_bytes()
-        ).expect("could not write");
-    
-        review_file.write_all(b"## Tasks \n").expect("could not write");
-        let mut current_project = String::new();
-    
+        let mut contents = format!(
+            "# {:02}/{:02}/{} Review \n\n## Tasks \n",
+            date.month(),
+            date.day(),
+            date.year()
+        );
+        let mut tasks = tasks;
+        tasks.sort_by(|a, b| a.project.split('/').cmp(b.project.split('/')));
+        let projects: Vec<String> = tasks.iter().map(|t| t.project.to_string()).collect();
+
+        // Areas get a heading of their own, counting the tasks below them
+        let mut headings: Vec<String> = Vec::new();
         for task in tasks {
-            
-            if task.project != current_project {
-                review_file.write_all(format!("\n#### {} \n", task.project).as_bytes()).expect("could not write");
-                current_project = task.project.to_string();
+            for area in crate::project::get_ancestors(&task.project) {
+                if headings.contains(&area) {
+                    continue;
+                }
+
+                let count = projects.iter().filter(|p| crate::project::is_within(p, &area)).count();
+                contents.push_str(&format!("\n#### {} ({}) \n", area, count));
+                headings.push(area);
             }
 
-            let new_project = &format!("{}/new", &task.project);
-            let new_path = task.path.replace(&task.project, &new_project);
-            review_file.write_all(
-                format!(
-                   "* [{}](../../../{}) \n",
-                   task.task_name,
-                   new_path,
-                ).as_bytes()
-            ).expect("failed to write");
-    
-            review_file.sync_data()?;
+            let new_path = task.get_location().with_status(true).get_relative_path();
+            contents.push_str(&format!(
+                "* [{}](../../../{}) \n",
+                link::escape_text(&task.task_name),
+                link::encode(&new_path),
+            ));
         }
     
-        Ok(())    
+        storage::write(&review_file_path, &contents)
     }
 }
 
-pub fn get_taskfolder(task_folder: &str, project: &str, is_new: bool) -> Result<String> {
-    let folderpath = format!(
-        "{}/{}/{}",
-        task_folder,
-        project,
-        if is_new { "new" } else { "" }
-    );
-
-    fs::create_dir_all(&folderpath)?;
+// Returns the task files of every year, newest first, or only those of
+// the year picked with `--year`
+fn get_task_files(new_only: bool, project: &str) -> Vec<String> {
+    let year = crate::setting::get_year();
+    let by_year = layout::has_year(&layout::get_template());
+
+    let mut files: Vec<(String, Location)> = layout::get_task_files()
+        .into_iter()
+        .filter(|(_f, l)| project.is_empty() || crate::project::matches(project, &l.project))
+        .filter(|(_f, l)| !new_only || l.is_new)
+        .filter(|(f, l)| match (year, by_year) {
+            (None, _) => true,
+            (Some(y), true) => l.year == Some(y),
+            // Templates without a year leave the created date to go by
+            (Some(y), false) => Task::get(f).map(|t| t.created.year() == y).unwrap_or(false),
+        })
+        .collect();
+
+    files.sort_by_key(|f| std::cmp::Reverse(f.1.year));
+    files.into_iter().map(|(f, _l)| f).collect()
+}
 
-    Ok(folderpath)
+fn create_parent_folder(file_path: &str) -> Result<()> {
+    match Path::new(file_path).parent() {
+        Some(folder) if !storage::exists(folder) => storage::create_dir_all(folder),
+        _ => Ok(()),
+    }
 }
 
-pub fn get_task_path_old(task_name: &str, project: &str, project_folder: &str) -> Result<String> {
-    let filepath = format!(
-        "{}/{}/new/{}.md",
-        project_folder,
-        project,
-        task_name
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+    use crate::task_list::TaskList;
 
-    let newpath = format!(
-        "{}/{}/{}.md",
-        project_folder,
-        project,
-        task_name
-    );
+    fn get_names(list: &str) -> Vec<String> {
+        TaskList::get(list).get_tasks().unwrap().into_iter().map(|t| t.task_name).collect()
+    }
 
-    let mut returnpath = newpath;
-    if Path::new(&filepath).exists() {
-        returnpath = filepath;
+    fn add_task(task_name: &str, project: &str, year: i32) -> Task {
+        let mut task = Task::new(task_name, project, &year);
+        task.created = Local.with_ymd_and_hms(year, 6, 1, 9, 0, 0).unwrap();
+        task.add("").unwrap();
+        task
     }
 
-    Ok(returnpath)
-}
+    fn get_files(root: &Root, new_only: bool, project: &str) -> Vec<String> {
+        let prefix = format!("{}/Projects/", root.path);
+        get_task_files(new_only, project)
+            .into_iter()
+            .map(|f| f.strip_prefix(&prefix).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn lists_newest_years_first() {
+        let root = Root::new();
+        add_task("Call Bob", "Home", 2024);
+        add_task("Write report", "Work", 2026);
+        add_task("Pay rent", "Home", 2025);
+        let listed = add_task("Fix fence", "Home", 2026);
+        TaskList::get("Today").add(listed).unwrap();
+
+        assert_eq!(get_files(&root, false, ""), vec![
+            "2026/Home/Fix fence.md",
+            "2026/Work/new/Write report.md",
+            "2025/Home/new/Pay rent.md",
+            "2024/Home/new/Call Bob.md",
+        ]);
+        assert_eq!(get_files(&root, true, "Home"), vec!["2025/Home/new/Pay rent.md", "2024/Home/new/Call Bob.md"]);
+    }
+
+    #[test]
+    fn lists_the_chosen_year() {
+        let root = Root::new();
+        add_task("Call Bob", "Home", 2024);
+        add_task("Pay rent", "Home", 2025);
+        add_task("Write report", "Work", 2025);
+
+        std::env::set_var("TODUIT_YEAR", "2025");
+        assert_eq!(get_files(&root, false, ""), vec!["2025/Home/new/Pay rent.md", "2025/Work/new/Write report.md"]);
+        assert_eq!(get_files(&root, false, "Work"), vec!["2025/Work/new/Write report.md"]);
+
+        std::env::set_var("TODUIT_YEAR", "2023");
+        assert!(get_files(&root, false, "").is_empty());
+    }
+
+    #[test]
+    fn lists_the_chosen_year_by_created_date_without_year_folders() {
+        let root = Root::new();
+        std::env::set_var("TODUIT_PATH_TEMPLATE", "{project}/{status}/{name}.md");
+        add_task("Call Bob", "Home", 2024);
+        add_task("Pay rent", "Home", 2025);
+
+        std::env::set_var("TODUIT_YEAR", "2024");
+        assert_eq!(get_files(&root, false, ""), vec!["Home/new/Call Bob.md"]);
+
+        std::env::remove_var("TODUIT_YEAR");
+        assert_eq!(get_files(&root, false, ""), vec!["Home/new/Call Bob.md", "Home/new/Pay rent.md"]);
+    }
 
+    #[test]
+    fn renames_list_entries() {
+        let root = Root::new();
+        let task = root.add_task("Call Bob", "Home");
+        TaskList::get("Today").add(task.clone()).unwrap();
+        TaskList::get("Today").add(root.add_task("Pay rent", "Home")).unwrap();
 
+        let task = Task::get_by_id_or_name(&task.id, false, "").unwrap();
+        task.rename_task("Call Robert").unwrap();
+
+        assert_eq!(get_names("Today"), vec!["Call Robert", "Pay rent"]);
+        assert!(!root.exists("Today/Call Bob.md"));
+        assert_eq!(fs_order(&root, "Today"), "Call Robert.md\nPay rent.md\n");
+
+        Task::get_by_id_or_name("Call Robert", false, "").unwrap().finish("Task Completed").unwrap();
+        assert_eq!(get_names("Today"), vec!["Pay rent"]);
+        assert_eq!(fs_order(&root, "Today"), "Pay rent.md\n");
+    }
+
+    #[test]
+    fn renames_new_tasks() {
+        let root = Root::new();
+        let task = root.add_task("Call Bob", "Home");
+        task.rename_task("Call Robert").unwrap();
+
+        let renamed = Task::get_by_id_or_name(&task.id, false, "").unwrap();
+        assert_eq!(renamed.task_name, "Call Robert");
+        assert_eq!(renamed.path, "Projects/2026/Home/Call Robert.md");
+        assert!(root.exists("Projects/2026/Home/new/Call Robert.md"));
+        assert!(!root.exists("Projects/2026/Home/new/Call Bob.md"));
+    }
+
+    #[test]
+    fn does_not_rename_over_other_tasks() {
+        let root = Root::new();
+        let task = root.add_task("Call Bob", "Home");
+        let other = root.add_task("Call Robert", "Home");
+        TaskList::get("Today").add(other).unwrap();
+
+        assert!(task.rename_task("Call Robert").is_err());
+        assert_eq!(Task::get_by_id_or_name(&task.id, false, "").unwrap().task_name, "Call Bob");
+        assert_eq!(get_names("Today"), vec!["Call Robert"]);
+    }
+
+    fn fs_order(root: &Root, list: &str) -> String {
+        std::fs::read_to_string(format!("{}/{}/.order", root.path, list)).unwrap_or_default()
+    }
+}
@@ -1,17 +1,20 @@
 extern crate chrono;
 
-use std::fs;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::{ BufReader, Result, Write };
-use std::path::{ Path, PathBuf};
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
 
 use pulldown_cmark::{Event, Options, Parser, Tag};
 use walkdir::WalkDir;
 
+use crate::layout;
+use crate::storage;
+use crate::util::link;
 use crate::task::Task;
 use crate::journal::*;
 
+const ORDER_FILE_NAME: &str = ".order";
+pub const TODAY_LIST: &str = "Today";
+
 #[derive(Clone)]
 pub struct TaskList {
     pub name: String,
@@ -27,83 +30,750 @@ impl TaskList {
     }
 
     pub fn add(&self, task: Task) -> Result<()> {
-        if Path::new(&self.path).exists() {
-            let listpath = format!("{}/{}.md", &self.path, &task.task_name);
-            let mut listfile = File::create(&listpath)?;
-            let task_link = format!("[{}](../{})", &task.task_name, &task.path);
-            listfile.write_all(task_link.as_bytes())?;
-            Task::change_task_folder(&task).expect("could not change task folder");
-            remove_from_lists(&task.task_name, &self.name);
-            
-            if &self.name == "Today" {
-                let journal = Journal::new("Current", "Journal")
-                    .expect("could not find journal");
-
-                journal.add_task_to_journal(&task);
+        self.check()?;
+        if let Some(limit) = self.get_limit() {
+            let is_listed = !get_entry_paths(&self.name, &task.task_name).is_empty();
+            if !is_listed && self.get_tasks()?.len() >= limit {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} is at its limit of {} task(s), finish or move one first or use --force", self.name, limit)
+                ));
             }
+        }
+
+        self.add_over_limit(task)
+    }
+
+    pub fn add_over_limit(&self, task: Task) -> Result<()> {
+        self.check()?;
+        if !storage::exists(&self.path) {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("list folder {} is missing, run `toduit init` to create it", self.path)
+            ));
+        }
+
+        let listpath = get_entry_path(&self.name, &task.task_name);
+        let is_listed = storage::exists(&listpath);
+        storage::write(&listpath, &get_task_link(&task))?;
 
-            task.add_comment(&format!("Added to list {}", &self.name), false)?;
-        }        
+        // New entries go to the bottom, re-adding keeps the place
+        if !is_listed {
+            let mut order: Vec<String> = self.get_entry_files()
+                .iter()
+                .map(|f| self.get_entry_name_for_file(f))
+                .filter(|e| *e != self.get_entry_name_for_file(&listpath))
+                .collect();
+            order.push(self.get_entry_name_for_file(&listpath));
+            self.save_order(&order)?;
+        }
+
+        Task::change_task_folder(&task)?;
+        remove_from_lists(&task, &self.name)?;
+
+        if self.name == TODAY_LIST {
+            let journal = Journal::new("Current", "Journal")?;
+            journal.add_task_to_journal(&task);
+        }
+
+        task.add_comment(&format!("Added to list {}", &self.name), false)?;
 
         Ok(())
     }
 
+    // Only the lists in the settings are looked at when moving tasks
+    // between lists, so any other folder is not a list
+    pub fn check(&self) -> Result<()> {
+        match get_names().contains(&self.name) {
+            true => Ok(()),
+            false => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("no list named {}, the lists are {}", self.name, get_names().join(", "))
+            )),
+        }
+    }
+
+    pub fn get_limit(&self) -> Option<usize> {
+        crate::setting::get_list_limit(&self.name)
+    }
+
+    // One task per entry, the way positions and the order file count them
     pub fn get_tasks(&self) -> Result<Vec<Task>> {
+        self.check()?;
+
         let mut tasks_list: Vec<Task> = Vec::new();
-        for entry in WalkDir::new(&self.path)
+        for list_path in self.get_entry_files() {
+            if let Some(task) = get_entry_task(&list_path)? {
+                tasks_list.push(task);
+            }
+        }
+
+        Ok(tasks_list)
+    }
+
+    // Positions count from 1, the way `list` prints them
+    pub fn get_task_at(&self, position: usize) -> Result<Task> {
+        let tasks = self.get_tasks()?;
+        match position.checked_sub(1).and_then(|i| tasks.get(i)) {
+            Some(task) => Ok(task.clone()),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} has {} task(s), there is no task {}", self.name, tasks.len(), position)
+            )),
+        }
+    }
+
+    pub fn move_task(&self, task: &Task, position: Position) -> Result<()> {
+        let entry = self.get_entry_name(task)?;
+        let mut order: Vec<String> = self.get_entry_files()
+            .iter()
+            .map(|f| self.get_entry_name_for_file(f))
+            .filter(|e| *e != entry)
+            .collect();
+
+        let index = match position {
+            Position::Top => 0,
+            Position::Bottom => order.len(),
+            Position::Before(other) | Position::After(other) if other.task_name == task.task_name => {
+                return Err(Error::new(ErrorKind::InvalidInput, "a task can not be moved next to itself"));
+            }
+            Position::Before(other) => self.get_index(&order, &other)?,
+            Position::After(other) => self.get_index(&order, &other)? + 1,
+        };
+
+        order.insert(index, entry);
+        self.save_order(&order)
+    }
+
+    fn get_index(&self, order: &[String], task: &Task) -> Result<usize> {
+        let entry = self.get_entry_name(task)?;
+        Ok(order.iter().position(|e| *e == entry).unwrap_or(order.len()))
+    }
+
+    // Entry files in the order kept in the order file. Entries it does not
+    // know about yet, like ones made by hand, come after it by name
+    fn get_entry_files(&self) -> Vec<String> {
+        let order = self.get_order();
+        let mut files: Vec<String> = WalkDir::new(&self.path)
             .follow_links(true)
             .into_iter()
-            .filter_map(|e| e.ok()) {
-                let list_path = entry.path().to_str().unwrap();
-                
-                if !list_path.ends_with(".md") {
-                    continue;
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.path().to_str().map(|p| p.to_string()))
+            .filter(|p| p.ends_with(".md") && storage::exists(p))
+            .collect();
+
+        files.sort_by_key(|f| {
+            let entry = self.get_entry_name_for_file(f);
+            (order.iter().position(|e| *e == entry).unwrap_or(order.len()), f.to_string())
+        });
+
+        files
+    }
+
+    fn get_entry_name(&self, task: &Task) -> Result<String> {
+        match get_entry_paths(&self.name, &task.task_name).first() {
+            Some(f) => Ok(self.get_entry_name_for_file(f)),
+            None => Err(Error::new(ErrorKind::NotFound, format!("{} is not on {}", task.task_name, self.name))),
+        }
+    }
+
+    fn get_entry_name_for_file(&self, file: &str) -> String {
+        file.trim_start_matches(&self.path).trim_start_matches('/').to_string()
+    }
+
+    fn get_order(&self) -> Vec<String> {
+        storage::read_to_string(format!("{}/{}", self.path, ORDER_FILE_NAME))
+            .unwrap_or_default()
+            .lines()
+            .map(|l| l.to_string())
+            .collect()
+    }
+
+    fn remove_from_order(&self, entry_files: &[String]) -> Result<()> {
+        let removed: Vec<String> = entry_files.iter().map(|f| self.get_entry_name_for_file(f)).collect();
+        let order = self.get_order();
+        if !order.iter().any(|e| removed.contains(e)) {
+            return Ok(());
+        }
+
+        let order: Vec<String> = order.into_iter().filter(|e| !removed.contains(e)).collect();
+        self.save_order(&order)
+    }
+
+    // A renamed entry keeps its place
+    fn rename_in_order(&self, entry_files: &[String], new_file: &str) -> Result<()> {
+        let renamed: Vec<String> = entry_files.iter().map(|f| self.get_entry_name_for_file(f)).collect();
+        let new_entry = self.get_entry_name_for_file(new_file);
+
+        let mut order: Vec<String> = Vec::new();
+        for entry in self.get_order() {
+            let entry = if renamed.contains(&entry) { new_entry.to_string() } else { entry };
+            if !order.contains(&entry) {
+                order.push(entry);
+            }
+        }
+
+        self.save_order(&order)
+    }
+
+    fn save_order(&self, order: &[String]) -> Result<()> {
+        let contents: String = order.iter().map(|e| format!("{}\n", e)).collect();
+        storage::write(format!("{}/{}", self.path, ORDER_FILE_NAME), &contents)
+    }
+}
+
+pub enum Position {
+    Top,
+    Bottom,
+    Before(Task),
+    After(Task),
+}
+
+// Tasks can be referred to by their position on a list, like `Today:2`
+pub fn get_task_at_reference(reference: &str) -> Option<Result<Task>> {
+    let (list, position) = reference.rsplit_once(':')?;
+    let position = position.parse::<usize>().ok()?;
+    let list = get_names().into_iter().find(|l| l.eq_ignore_ascii_case(list))?;
+
+    Some(TaskList::get(&list).get_task_at(position))
+}
+
+pub fn get_names() -> Vec<String> {
+    crate::setting::get_todo_list()
+        .split(',')
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_string())
+        .collect()
+}
+
+// The functions below change the set of lists and return it, for the
+// caller to save to the settings
+pub fn create_list(name: &str) -> Result<Vec<String>> {
+    validate_list_name(name)?;
+
+    let mut names = get_names();
+    if names.iter().any(|l| l == name) {
+        return Err(Error::new(ErrorKind::AlreadyExists, format!("list {} already exists", name)));
+    }
+
+    storage::create_dir_all(TaskList::get(name).path)?;
+    names.push(name.to_string());
+    set_names(&names);
+
+    Ok(names)
+}
+
+// Tasks on a deleted list go back to the new folder, otherwise they would
+// look finished
+pub fn delete_list(name: &str, force: bool) -> Result<Vec<String>> {
+    let list = TaskList::get(name);
+    list.check()?;
+    check_is_removable(name)?;
+
+    let tasks = list.get_tasks()?;
+    if !tasks.is_empty() && !force {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("list {} has {} task(s), use --force to unlist them and delete it", name, tasks.len())
+        ));
+    }
+
+    for task in tasks {
+        task.unlist()?;
+    }
+
+    for file in get_list_files(&list.path) {
+        storage::remove_file(file)?;
+    }
+
+    if storage::exists(&list.path) {
+        storage::remove_dir(&list.path)?;
+    }
+
+    crate::project::rename_default_list(name, None)?;
+    let names: Vec<String> = get_names().into_iter().filter(|l| l != name).collect();
+    set_names(&names);
+
+    Ok(names)
+}
+
+pub fn rename_list(name: &str, new_name: &str) -> Result<Vec<String>> {
+    let list = TaskList::get(name);
+    list.check()?;
+    check_is_removable(name)?;
+    validate_list_name(new_name)?;
+
+    let new_list = TaskList::get(new_name);
+    if get_names().iter().any(|l| l == new_name) || storage::exists(&new_list.path) {
+        return Err(Error::new(ErrorKind::AlreadyExists, format!("list {} already exists", new_name)));
+    }
+
+    storage::create_dir_all(&new_list.path)?;
+    for file in get_list_files(&list.path) {
+        let new_file = format!("{}{}", new_list.path, &file[list.path.len()..]);
+        storage::rename(&file, &new_file)?;
+    }
+
+    if storage::exists(&list.path) {
+        storage::remove_dir(&list.path)?;
+    }
+
+    crate::project::rename_default_list(name, Some(new_name))?;
+    let names: Vec<String> = get_names()
+        .into_iter()
+        .map(|l| if l == name { new_name.to_string() } else { l })
+        .collect();
+    set_names(&names);
+
+    Ok(names)
+}
+
+pub fn reorder_lists(order: &[String]) -> Result<Vec<String>> {
+    let mut sorted_order = order.to_vec();
+    let mut names = get_names();
+    sorted_order.sort();
+    names.sort();
+
+    if sorted_order != names {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("the new order must name every list once, the lists are {}", get_names().join(", "))
+        ));
+    }
+
+    set_names(order);
+    Ok(order.to_vec())
+}
+
+fn set_names(names: &[String]) {
+    std::env::set_var("TODUIT_TODO_LISTS", names.join(","));
+}
+
+fn get_list_files(list_path: &str) -> Vec<String> {
+    WalkDir::new(list_path)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.path().to_str().map(|p| p.to_string()))
+        .filter(|p| storage::exists(p))
+        .collect()
+}
+
+// Today is where the journal and reminders put tasks, and the commands
+// that wait on people and plan the day look for their lists by name
+fn check_is_removable(name: &str) -> Result<()> {
+    let used_by = match name {
+        TODAY_LIST => "the journal and reminders",
+        crate::waiting::WAITING_LIST => "wait and waiting",
+        crate::plan::QUEUED_LIST => "plan",
+        _ => return Ok(()),
+    };
+
+    Err(Error::new(
+        ErrorKind::InvalidInput,
+        format!("{} is used by {} and can not be renamed or deleted", name, used_by)
+    ))
+}
+
+fn validate_list_name(name: &str) -> Result<()> {
+    let folder_names = [
+        crate::setting::get_project_folder_name(),
+        crate::setting::get_journal_folder_name(),
+        crate::setting::get_review_folder_name(),
+        crate::setting::get_archive_folder_name(),
+    ];
+
+    let invalid = name.trim().is_empty()
+        || name != name.trim()
+        || name.starts_with('.')
+        || name.contains(|c| ['/', '\\', ',', '=', '\'', '"'].contains(&c));
+
+    if invalid {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{:?} is not a valid list name", name)));
+    }
+
+    if folder_names.iter().any(|f| f == name) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} is already used for another folder", name)));
+    }
+
+    Ok(())
+}
+
+pub fn remove_from_lists(task: &Task, excluded_list: &str) -> Result<()> {
+    for list in get_names() {
+        if list != excluded_list {
+            let entry_files = get_entry_paths(&list, &task.task_name);
+            for filepath in &entry_files {
+                storage::remove_file(filepath)?;
+            }
+
+            TaskList::get(&list).remove_from_order(&entry_files)?;
+            if list == crate::waiting::WAITING_LIST && !entry_files.is_empty() {
+                clear_waiting(task)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Removes a single entry file, like one whose task is gone, and its line
+// in the order file of the list it is on
+pub fn remove_entry(entry_path: &str) -> Result<()> {
+    storage::remove_file(entry_path)?;
+
+    let list = get_names()
+        .into_iter()
+        .map(|l| TaskList::get(&l))
+        .find(|l| entry_path.starts_with(&format!("{}/", l.path)));
+
+    match list {
+        Some(l) => l.remove_from_order(&[entry_path.to_string()]),
+        None => Ok(()),
+    }
+}
+
+// Entries are named after the task, so a renamed task gets new entries
+// with a link to its new file
+pub fn rename_in_lists(task: &Task, old_name: &str) -> Result<()> {
+    for list in get_names() {
+        let entry_files = get_entry_paths(&list, old_name);
+        if entry_files.is_empty() {
+            continue;
+        }
+
+        let new_file = get_entry_path(&list, &task.task_name);
+        storage::write(&new_file, &get_task_link(task))?;
+        for filepath in entry_files.iter().filter(|f| **f != new_file) {
+            storage::remove_file(filepath)?;
+        }
+
+        TaskList::get(&list).rename_in_order(&entry_files, &new_file)?;
+    }
+
+    Ok(())
+}
+
+// Who a task waits on only means something while it is on Waiting
+fn clear_waiting(task: &Task) -> Result<()> {
+    let mut saved = Task::get(&task.get_file_path())?;
+    if saved.waiting_on.is_none() && saved.follow_up.is_none() {
+        return Ok(());
+    }
+
+    saved.waiting_on = None;
+    saved.follow_up = None;
+    saved.save()
+}
+
+pub fn get_lists_for_task(task_name: &str) -> Vec<String> {
+    get_names()
+        .into_iter()
+        .filter(|list| !get_entry_paths(list, task_name).is_empty())
+        .collect()
+}
+
+pub fn get_entry_path(list: &str, task_name: &str) -> String {
+    format!(
+        "{}/{}/{}.md",
+        crate::setting::get_root_folder(),
+        list,
+        layout::get_safe_file_name(task_name)
+    )
+}
+
+// Entries used to be named after the raw task name, which is still found
+// for names that were valid file names here
+fn get_entry_paths(list: &str, task_name: &str) -> Vec<String> {
+    let mut paths = vec![get_entry_path(list, task_name)];
+    if !task_name.contains('/') {
+        paths.push(format!("{}/{}/{}.md", crate::setting::get_root_folder(), list, task_name));
+    }
+
+    paths.dedup();
+    paths.into_iter().filter(|p| storage::exists(p)).collect()
+}
+
+pub fn get_task_link(task: &Task) -> String {
+    format!("[{}](../{})", link::escape_text(&task.task_name), link::encode(&task.path))
+}
+
+// An entry is named after its task, so other links in it do not put
+// those tasks on the list
+fn get_entry_task(entry_path: &str) -> Result<Option<Task>> {
+    let mut tasks = get_entry_tasks(entry_path)?;
+    if tasks.is_empty() {
+        return Ok(None);
+    }
+
+    let stem = Path::new(entry_path).file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let index = tasks.iter()
+        .position(|(name, _task)| name == stem || layout::get_safe_file_name(name) == stem)
+        .unwrap_or(0);
+
+    tasks.swap_remove(index).1.map(Some)
+}
+
+// Returns the tasks linked from a list entry file. Links are followed by
+// path, falling back to the task name for links left behind by a move
+pub fn get_entry_tasks(entry_path: &str) -> Result<Vec<(String, Result<Task>)>> {
+    let contents = encode_link_spaces(&storage::read_to_string(entry_path)?);
+
+    let mut tasks: Vec<(String, Result<Task>)> = Vec::new();
+    let mut link: Option<(String, String)> = None;
+    for event in Parser::new_ext(&contents, Options::empty()) {
+        match event {
+            Event::Start(Tag::Link(_, dest, _)) => link = Some((dest.to_string(), String::new())),
+            Event::Text(text) => {
+                if let Some((_dest, name)) = link.as_mut() {
+                    name.push_str(&text);
                 }
-                
-                let p = PathBuf::from(&list_path);
-                let task_file = File::open(p).expect("could not open file");
-                let mut contents = String::new();
-                let mut buf_reader = BufReader::new(task_file);
-
-                buf_reader.read_to_string(&mut contents)?;
-                // Markdown parser is unable to read links
-                // with spaces in the path
-                // Replacing with encoded space to be removed
-                // before opening the file later
-                contents = contents.replace(" ", "%20");
-
-                let options = Options::empty();
-                let parser = Parser::new_ext(&contents, options);
-
-                let ps: Vec<Event> = parser.collect();
-                for p in &ps {
-                    if let Event::Start(Tag::Link(_, dest, _)) = p {
-                        let entries: Vec<&str> = dest.split_terminator("/").collect();
-                        let task_name = entries[entries.len() -1]
-                            .replace("%20", " ")
-                            .replace(".md", "");
-
-                        let etask = Task::get_by_id_or_name(&task_name, false, "")
-                            .expect("could not get task");
-
-                        tasks_list.push(etask);
-                    }
+            }
+            Event::End(Tag::Link(..)) => {
+                if let Some((dest, name)) = link.take() {
+                    let task_path = link::decode(dest.trim_start_matches("../"));
+                    let task = Task::get_by_path(&task_path)
+                        .or_else(|_e| Task::get_by_id_or_name(&name, false, ""));
+
+                    tasks.push((name, task));
                 }
+            }
+            _ => (),
         }
+    }
 
-        Ok(tasks_list)
+    Ok(tasks)
+}
+
+// Older links were written without encoding, so the spaces in their
+// targets are encoded for the markdown parser. The link text is kept as
+// it is, since task names can contain anything
+fn encode_link_spaces(contents: &str) -> String {
+    let mut encoded = String::new();
+    let mut rest = contents;
+
+    while let Some(start) = rest.find("](") {
+        let (before, after) = rest.split_at(start + 2);
+        encoded.push_str(before);
+        let escapes = before[..start].chars().rev().take_while(|c| *c == '\\').count();
+        if escapes % 2 == 1 {
+            rest = after;
+            continue;
+        }
+
+        let end = after.find(')').unwrap_or(after.len());
+        encoded.push_str(&after[..end].replace(' ', "%20"));
+        rest = &after[end..];
     }
+
+    encoded.push_str(rest);
+    encoded
 }
 
-pub fn remove_from_lists(task_name: &str, excluded_list: &str) {
-    let root_folder = crate::setting::get_root_folder();
-    let valid_list = crate::setting::get_todo_list();
-    for list in valid_list.to_string().split(",") {
-        if list.to_string() != excluded_list {
-            let filepath = format!("{}/{}/{}.md", root_folder, list.to_string(), task_name);
-            if Path::new(&filepath).exists() {
-                fs::remove_file(filepath).expect("could not remove from list");
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+    use crate::project::Project;
+
+    fn to_strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn get_task_names(list: &str) -> Vec<String> {
+        TaskList::get(list).get_tasks().unwrap().into_iter().map(|t| t.task_name).collect()
+    }
+
+    fn add_project(name: &str, default_list: &str) {
+        let mut project = Project::new(name);
+        project.default_list = Some(default_list.to_string());
+        project.create().unwrap();
+    }
+
+    #[test]
+    fn creates_lists() {
+        let root = Root::new();
+        assert_eq!(create_list("Later").unwrap(), to_strings(&["Today", "Queued", "Waiting", "Someday", "Later"]));
+        assert!(root.exists("Later"));
+        assert_eq!(get_names().last().unwrap(), "Later");
+
+        assert_eq!(create_list("Later").unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(create_list("Projects").unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn validates_list_names() {
+        let _root = Root::new();
+        for name in &["Later", "Next week", "Errands 2"] {
+            assert!(validate_list_name(name).is_ok(), "{}", name);
+        }
+
+        let invalid = ["", " ", " Later", "Later ", ".hidden", "a/b", "a\\b", "a,b", "a=b", "a'b", "a\"b", "Journal", "Review", "Archive"];
+        for name in &invalid {
+            assert_eq!(validate_list_name(name).unwrap_err().kind(), ErrorKind::InvalidInput, "{}", name);
+        }
+    }
+
+    #[test]
+    fn deletes_lists() {
+        let root = Root::new();
+        TaskList::get("Someday").add(root.add_task("Learn piano", "Home")).unwrap();
+        add_project("Home", "Someday");
+        add_project("Work", "Today");
+
+        assert_eq!(delete_list("Someday", false).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(get_task_names("Someday"), vec!["Learn piano"]);
+
+        assert_eq!(delete_list("Someday", true).unwrap(), to_strings(&["Today", "Queued", "Waiting"]));
+        assert!(!root.exists("Someday"));
+        assert!(root.exists("Projects/2026/Home/new/Learn piano.md"));
+        assert_eq!(Project::get("Home").unwrap().default_list, None);
+        assert_eq!(Project::get("Work").unwrap().default_list, Some("Today".to_string()));
+
+        assert_eq!(delete_list("Someday", true).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(delete_list("Today", true).unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn renames_lists() {
+        let root = Root::new();
+        TaskList::get("Someday").add(root.add_task("Learn piano", "Home")).unwrap();
+        TaskList::get("Someday").add(root.add_task("Paint shed", "Home")).unwrap();
+        TaskList::get("Someday").move_task(&Task::get_by_id_or_name("Paint shed", false, "").unwrap(), Position::Top).unwrap();
+        add_project("Home", "Someday");
+
+        assert_eq!(rename_list("Someday", "Later").unwrap(), to_strings(&["Today", "Queued", "Waiting", "Later"]));
+        assert!(!root.exists("Someday"));
+        assert_eq!(get_task_names("Later"), vec!["Paint shed", "Learn piano"]);
+        assert_eq!(get_lists_for_task("Learn piano"), vec!["Later"]);
+        assert_eq!(Project::get("Home").unwrap().default_list, Some("Later".to_string()));
+
+        assert_eq!(rename_list("Later", "Today").unwrap_err().kind(), ErrorKind::AlreadyExists);
+        assert_eq!(rename_list("Later", "a/b").unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(rename_list("Waiting", "On hold").unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(rename_list("Someday", "Never").unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn reorders_lists() {
+        let _root = Root::new();
+        let order = to_strings(&["Someday", "Today", "Waiting", "Queued"]);
+        assert_eq!(reorder_lists(&order).unwrap(), order);
+        assert_eq!(get_names(), order);
+
+        for order in &[vec!["Today", "Queued", "Waiting"], vec!["Today", "Queued", "Waiting", "Someday", "Today"], vec!["Today", "Queued", "Waiting", "Later"]] {
+            assert_eq!(reorder_lists(&to_strings(order)).unwrap_err().kind(), ErrorKind::InvalidInput);
+        }
+    }
+
+    fn fill_today(root: &Root, task_names: &[&str]) -> Vec<Task> {
+        let today = TaskList::get("Today");
+        for task_name in task_names {
+            today.add(root.add_task(task_name, "Home")).unwrap();
         }
+
+        today.get_tasks().unwrap()
+    }
+
+    #[test]
+    fn moves_tasks() {
+        let root = Root::new();
+        let tasks = fill_today(&root, &["One", "Two", "Three", "Four"]);
+        let today = TaskList::get("Today");
+
+        today.move_task(&tasks[2], Position::Top).unwrap();
+        assert_eq!(get_task_names("Today"), vec!["Three", "One", "Two", "Four"]);
+
+        today.move_task(&tasks[2], Position::Bottom).unwrap();
+        assert_eq!(get_task_names("Today"), vec!["One", "Two", "Four", "Three"]);
+
+        today.move_task(&tasks[3], Position::Before(tasks[0].clone())).unwrap();
+        assert_eq!(get_task_names("Today"), vec!["Four", "One", "Two", "Three"]);
+
+        today.move_task(&tasks[3], Position::After(tasks[2].clone())).unwrap();
+        assert_eq!(get_task_names("Today"), vec!["One", "Two", "Three", "Four"]);
+        assert_eq!(std::fs::read_to_string(format!("{}/Today/.order", root.path)).unwrap(), "One.md\nTwo.md\nThree.md\nFour.md\n");
+
+        let elsewhere = root.add_task("Five", "Home");
+        assert_eq!(today.move_task(&tasks[0], Position::Before(tasks[0].clone())).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(today.move_task(&tasks[0], Position::After(elsewhere.clone())).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(today.move_task(&elsewhere, Position::Top).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(get_task_names("Today"), vec!["One", "Two", "Three", "Four"]);
+    }
+
+    #[test]
+    fn puts_unordered_entries_last() {
+        let root = Root::new();
+        let tasks = fill_today(&root, &["Two", "One"]);
+        TaskList::get("Today").move_task(&tasks[1], Position::Top).unwrap();
+        std::fs::copy(format!("{}/Today/One.md", root.path), format!("{}/Today/Another.md", root.path)).unwrap();
+
+        assert_eq!(get_task_names("Today"), vec!["One", "Two", "One"]);
+    }
+
+    #[test]
+    fn finds_tasks_by_position() {
+        let root = Root::new();
+        fill_today(&root, &["One", "Two"]);
+        let today = TaskList::get("Today");
+
+        assert_eq!(today.get_task_at(1).unwrap().task_name, "One");
+        assert_eq!(today.get_task_at(2).unwrap().task_name, "Two");
+        assert_eq!(today.get_task_at(0).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(today.get_task_at(3).unwrap_err().kind(), ErrorKind::NotFound);
+
+        assert_eq!(get_task_at_reference("Today:2").unwrap().unwrap().task_name, "Two");
+        assert_eq!(get_task_at_reference("today:1").unwrap().unwrap().task_name, "One");
+        assert_eq!(get_task_at_reference("Today:3").unwrap().unwrap_err().kind(), ErrorKind::NotFound);
+        assert!(get_task_at_reference("Queued:1").unwrap().is_err());
+        assert!(get_task_at_reference("Later:1").is_none());
+        assert!(get_task_at_reference("Today:first").is_none());
+        assert!(get_task_at_reference("Meeting: agenda").is_none());
+        assert!(get_task_at_reference("One").is_none());
+    }
+
+    #[test]
+    fn keeps_lists_to_their_limit() {
+        let root = Root::new();
+        std::env::set_var("TODUIT_LIST_LIMITS", "Waiting=1");
+        let tasks = fill_today(&root, &["One", "Two", "Three"]);
+        let waiting = TaskList::get("Waiting");
+
+        tasks[0].wait("Alice", None, false).unwrap();
+        assert_eq!(tasks[1].wait("Bob", None, false).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(waiting.add(tasks[1].clone()).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(get_task_names("Today"), vec!["Two", "Three"]);
+
+        // Adding a task that is already on the list does not count twice
+        waiting.add(tasks[0].clone()).unwrap();
+        tasks[1].wait("Bob", None, true).unwrap();
+        waiting.add_over_limit(tasks[2].clone()).unwrap();
+        assert_eq!(get_task_names("Waiting"), vec!["One", "Two", "Three"]);
+        assert_eq!(Task::get_by_id_or_name("Two", false, "").unwrap().waiting_on, Some("Bob".to_string()));
+    }
+
+    #[test]
+    fn encodes_spaces_in_link_targets() {
+        assert_eq!(
+            encode_link_spaces("[Fix the fence](../Projects/2026/Home/Fix the fence.md)"),
+            "[Fix the fence](../Projects/2026/Home/Fix%20the%20fence.md)"
+        );
+        assert_eq!(
+            encode_link_spaces("[50%20 off](../Projects/2026/Home/50%2520%20off.md)"),
+            "[50%20 off](../Projects/2026/Home/50%2520%20off.md)"
+        );
+    }
+
+    #[test]
+    fn keeps_escaped_brackets_in_link_text() {
+        assert_eq!(
+            encode_link_spaces("[a\\](b c](../Projects/a](b c.md)"),
+            "[a\\](b c](../Projects/a](b%20c.md)"
+        );
+        assert_eq!(
+            encode_link_spaces("[a\\\\](../Projects/a b.md)"),
+            "[a\\\\](../Projects/a%20b.md)"
+        );
     }
 }
@@ -0,0 +1,357 @@
+extern crate chrono;
+extern crate serde_json;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::io::{Error, ErrorKind, Result};
+
+use crate::project;
+use crate::task::Task;
+use crate::task_list::{self, TaskList};
+
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskwarriorTask {
+    pub uuid: String,
+    pub description: String,
+    pub status: String,
+    pub entry: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Annotation {
+    pub entry: String,
+    pub description: String,
+}
+
+impl TaskwarriorTask {
+    pub fn from_task(task: &Task) -> Result<TaskwarriorTask> {
+        let finish = match task.is_finished() {
+            true => Some(task.get_finish().unwrap_or((task.updated, "Task Completed".to_string()))),
+            false => None,
+        };
+
+        let mut tags = task_list::get_lists_for_task(&task.task_name);
+        if let Some(t) = &task.tags {
+            tags.extend(t.iter().cloned());
+        }
+
+        // The log is stored newest first while annotations are chronological.
+        // The finish is the status and end, earlier ones stay annotations
+        let annotations: Vec<Annotation> = task.get_comments()?
+            .into_iter()
+            .rev()
+            .filter(|(_, c)| c != "Task Created")
+            .filter(|comment| Some(comment) != finish.as_ref())
+            .map(|(date, c)| Annotation { entry: format_date(&date), description: c })
+            .collect();
+
+        let status = match &finish {
+            None => "pending",
+            Some((_d, c)) if c == "Task Canceled" => "deleted",
+            Some(_f) => "completed",
+        };
+
+        let due = task.remind.as_ref().and_then(|r| {
+            let date = r.get_date()?;
+            let time = r.get_time().unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+            Local.from_local_datetime(&date.and_time(time)).single()
+        });
+
+        Ok(TaskwarriorTask {
+            uuid: task.id.to_string(),
+            description: task.task_name.to_string(),
+            status: status.to_string(),
+            entry: format_date(&task.created),
+            project: Some(task.project.to_string()),
+            modified: Some(format_date(&task.updated)),
+            end: finish.as_ref().map(|(d, _c)| format_date(d)),
+            due: due.map(|d| format_date(&d)),
+            priority: task.priority.as_ref().map(|p| from_priority(p)),
+            tags,
+            annotations,
+        })
+    }
+}
+
+pub fn export(tasks: &[Task]) -> Result<String> {
+    let mut tw_tasks: Vec<TaskwarriorTask> = Vec::new();
+    for task in tasks {
+        tw_tasks.push(TaskwarriorTask::from_task(task)?);
+    }
+
+    serde_json::to_string_pretty(&tw_tasks)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+pub fn import(contents: &str, project: &str) -> Result<Vec<Task>> {
+    let tw_tasks: Vec<TaskwarriorTask> = serde_json::from_str(contents)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+    let mut imported: Vec<Task> = Vec::new();
+    let year = crate::setting::get_year().unwrap_or_else(|| Local::now().year());
+    let valid_list = crate::setting::get_todo_list();
+    let lists: Vec<&str> = valid_list.split(',').collect();
+
+    for tw_task in tw_tasks {
+        if tw_task.description.is_empty() || tw_task.status == "recurring" {
+            continue;
+        }
+
+        if !tw_task.uuid.is_empty() && Task::get_by_id_or_name(&tw_task.uuid, false, "").is_ok() {
+            continue;
+        }
+
+        let task_project = project::get_import_name(project, tw_task.project.as_deref())?;
+
+        if Task::get_by_id_or_name(&tw_task.description, false, &task_project).is_ok() {
+            eprintln!("skipping {}, a task with that name already exists", tw_task.description);
+            continue;
+        }
+
+        let (task_lists, tags): (Vec<String>, Vec<String>) = tw_task.tags
+            .iter()
+            .cloned()
+            .partition(|t| lists.contains(&t.as_str()));
+
+        let mut task = Task::new(&tw_task.description, &task_project, &year);
+        if !tw_task.uuid.is_empty() {
+            task.id = tw_task.uuid.to_string();
+        }
+
+        if let Some(entry) = parse_date(&tw_task.entry) {
+            task.created = entry;
+        }
+
+        let modified = tw_task.modified.as_ref().and_then(|m| parse_date(m));
+        if let Some(m) = modified {
+            task.updated = m;
+        }
+
+        task.priority = tw_task.priority.as_ref().map(|p| to_priority(p));
+        if !tags.is_empty() {
+            task.tags = Some(tags);
+        }
+
+        task.add("")?;
+
+        for annotation in &tw_task.annotations {
+            let entry = parse_date(&annotation.entry).unwrap_or_else(Local::now);
+            task.add_comment_at(&annotation.description, true, entry)?;
+        }
+
+        let end = tw_task.end.as_ref()
+            .and_then(|e| parse_date(e))
+            .or(modified)
+            .unwrap_or_else(Local::now);
+
+        match tw_task.status.as_str() {
+            "completed" => task.finish_at("Task Completed", end)?,
+            "deleted" => task.finish_at("Task Canceled", end)?,
+            _ => {
+                if let Some(due) = tw_task.due.as_ref().and_then(|d| parse_date(d)) {
+                    let time = if due.hour() == 0 && due.minute() == 0 {
+                        "".to_string()
+                    } else {
+                        due.format("%H:%M").to_string()
+                    };
+
+                    task.set_reminder(
+                        &due.format("%m").to_string(),
+                        &due.format("%d").to_string(),
+                        &due.format("%Y").to_string(),
+                        &time,
+                        &0
+                    )?;
+                }
+
+                for list in &task_lists {
+                    if let Err(e) = TaskList::get(list).add(task.clone()) {
+                        eprintln!("imported {} but could not add it to {} with error {}", task.task_name, list, e);
+                    }
+                }
+            }
+        }
+
+        // Setting the reminder saves the task, which would make it look changed now
+        if let Some(m) = modified {
+            Task::get(&task.get_file_path())?.save_at(m)?;
+        }
+
+        imported.push(task);
+    }
+
+    Ok(imported)
+}
+
+fn format_date(date: &DateTime<Local>) -> String {
+    date.with_timezone(&Utc).format(DATE_FORMAT).to_string()
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(value, DATE_FORMAT)
+        .ok()
+        .map(|d| Utc.from_utc_datetime(&d).with_timezone(&Local))
+}
+
+// Taskwarrior uses H, M and L where todo.txt style priorities are letters
+fn from_priority(priority: &str) -> String {
+    match priority {
+        "A" => "H",
+        "B" => "M",
+        _ => "L",
+    }.to_string()
+}
+
+fn to_priority(priority: &str) -> String {
+    match priority {
+        "H" => "A",
+        "M" => "B",
+        _ => "C",
+    }.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+
+    const TASKS: &str = r#"[
+        {
+            "uuid": "b2b1f1c4-0000-4000-8000-000000000001",
+            "description": "Call Bob",
+            "status": "pending",
+            "entry": "20261001T120000Z",
+            "modified": "20261002T120000Z",
+            "project": "Home",
+            "priority": "H",
+            "due": "20261020T150000Z",
+            "tags": ["Today", "phone"],
+            "annotations": [
+                { "entry": "20261001T130000Z", "description": "left a message" },
+                { "entry": "20261001T140000Z", "description": "called back" }
+            ]
+        },
+        {
+            "uuid": "b2b1f1c4-0000-4000-8000-000000000002",
+            "description": "Pay rent",
+            "status": "completed",
+            "entry": "20261001T120000Z",
+            "end": "20261003T120000Z",
+            "modified": "20261003T120000Z",
+            "project": "Home",
+            "priority": "M"
+        },
+        {
+            "uuid": "b2b1f1c4-0000-4000-8000-000000000003",
+            "description": "Old idea",
+            "status": "deleted",
+            "entry": "20261001T120000Z",
+            "end": "20261004T120000Z",
+            "modified": "20261004T120000Z",
+            "priority": "L"
+        },
+        { "description": "", "status": "pending", "entry": "20261001T120000Z" },
+        { "description": "Every week", "status": "recurring", "entry": "20261001T120000Z" }
+    ]"#;
+
+    #[test]
+    fn round_trips_tasks() {
+        let _root = Root::new();
+        let imported = import(TASKS, "").unwrap();
+        assert_eq!(imported.iter().map(|t| t.task_name.as_str()).collect::<Vec<&str>>(), vec!["Call Bob", "Pay rent", "Old idea"]);
+
+        let tasks: Vec<Task> = imported.iter().map(|t| Task::get_by_id_or_name(&t.id, false, "").unwrap()).collect();
+        let exported: Vec<TaskwarriorTask> = serde_json::from_str(&export(&tasks).unwrap()).unwrap();
+        let original: Vec<TaskwarriorTask> = serde_json::from_str(TASKS).unwrap();
+
+        for (before, after) in original.iter().zip(&exported) {
+            assert_eq!(after.uuid, before.uuid);
+            assert_eq!(after.description, before.description);
+            assert_eq!(after.status, before.status);
+            assert_eq!(after.entry, before.entry);
+            assert_eq!(after.modified, before.modified);
+            assert_eq!(after.end, before.end);
+            assert_eq!(after.due, before.due);
+            assert_eq!(after.priority, before.priority);
+            assert_eq!(after.tags, before.tags);
+            // Adding the task to its lists on import logs that as well
+            assert_eq!(
+                after.annotations.iter()
+                    .filter(|a| !a.description.starts_with("Added to list"))
+                    .map(|a| (&a.entry, &a.description))
+                    .collect::<Vec<_>>(),
+                before.annotations.iter().map(|a| (&a.entry, &a.description)).collect::<Vec<_>>()
+            );
+        }
+
+        assert_eq!(exported[2].project, Some("Inbox".to_string()));
+        assert_eq!(TaskList::get("Today").get_tasks().unwrap()[0].task_name, "Call Bob");
+    }
+
+    #[test]
+    fn skips_tasks_already_imported() {
+        let _root = Root::new();
+        assert_eq!(import(TASKS, "Work").unwrap().len(), 3);
+        assert_eq!(import(TASKS, "Work").unwrap().len(), 0);
+        assert_eq!(Task::get_by_id_or_name("Call Bob", false, "").unwrap().project, "Work");
+    }
+
+    #[test]
+    fn exports_annotations_in_order() {
+        let root = Root::new();
+        let task = root.add_task("Call Bob", "Home");
+        let first = Local.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap();
+        let second = Local.with_ymd_and_hms(2026, 10, 6, 9, 0, 0).unwrap();
+        task.add_comment_at("left a message", true, first).unwrap();
+        task.add_comment_at("called back", true, second).unwrap();
+
+        let exported = TaskwarriorTask::from_task(&task).unwrap();
+        assert_eq!(exported.status, "pending");
+        let annotations: Vec<(&str, &str)> = exported.annotations.iter().map(|a| (a.entry.as_str(), a.description.as_str())).collect();
+        assert_eq!(annotations, vec![
+            (format_date(&first).as_str(), "left a message"),
+            (format_date(&second).as_str(), "called back"),
+        ]);
+    }
+
+    #[test]
+    fn maps_priorities() {
+        for (letter, level) in &[("A", "H"), ("B", "M"), ("C", "L")] {
+            assert_eq!(from_priority(letter), *level);
+            assert_eq!(to_priority(level), *letter);
+        }
+
+        assert_eq!(from_priority("D"), "L");
+        assert_eq!(to_priority(""), "C");
+    }
+
+    #[test]
+    fn parses_dates() {
+        let date = parse_date("20261019T153000Z").unwrap();
+        assert_eq!(date.with_timezone(&Utc), Utc.with_ymd_and_hms(2026, 10, 19, 15, 30, 0).unwrap());
+        assert_eq!(format_date(&date), "20261019T153000Z");
+
+        assert!(parse_date("2026-10-19").is_none());
+        assert!(parse_date("20261019T153000").is_none());
+        assert!(parse_date("").is_none());
+    }
+}
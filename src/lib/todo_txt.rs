@@ -0,0 +1,310 @@
+extern crate chrono;
+
+use chrono::prelude::*;
+use std::io::Result;
+
+use crate::project;
+use crate::task::Task;
+use crate::task_list::{self, TaskList};
+use crate::util::link;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TodoItem {
+    pub completed: bool,
+    pub priority: Option<char>,
+    pub completion_date: Option<NaiveDate>,
+    pub creation_date: Option<NaiveDate>,
+    pub description: String,
+    pub projects: Vec<String>,
+    pub contexts: Vec<String>,
+    pub due: Option<NaiveDate>,
+}
+
+impl TodoItem {
+    pub fn parse(line: &str) -> TodoItem {
+        let mut item = TodoItem::default();
+        let mut tokens: Vec<&str> = line.split_whitespace().collect();
+
+        if tokens.first() == Some(&"x") {
+            item.completed = true;
+            tokens.remove(0);
+            if let Some(date) = tokens.first().and_then(|t| parse_date(t)) {
+                item.completion_date = Some(date);
+                tokens.remove(0);
+            }
+        } else if let Some(priority) = tokens.first().and_then(|t| parse_priority(t)) {
+            item.priority = Some(priority);
+            tokens.remove(0);
+        }
+
+        if let Some(date) = tokens.first().and_then(|t| parse_date(t)) {
+            item.creation_date = Some(date);
+            tokens.remove(0);
+        }
+
+        let mut description: Vec<&str> = Vec::new();
+        for token in tokens {
+            if token.len() > 1 && token.starts_with('+') {
+                item.projects.push(token[1..].to_string());
+            } else if token.len() > 1 && token.starts_with('@') {
+                item.contexts.push(token[1..].to_string());
+            } else if let Some(date) = token.strip_prefix("due:").and_then(parse_date) {
+                item.due = Some(date);
+            } else {
+                description.push(token);
+            }
+        }
+
+        item.description = description.join(" ");
+        item
+    }
+
+    pub fn from_task(task: &Task) -> TodoItem {
+        let finished = match task.is_finished() {
+            true => Some(task.get_finish().map(|(d, _c)| d).unwrap_or(task.updated)),
+            false => None,
+        };
+
+        TodoItem::from_task_on_lists(task, task_list::get_lists_for_task(&task.task_name), finished)
+    }
+
+    fn from_task_on_lists(task: &Task, lists: Vec<String>, finished: Option<DateTime<Local>>) -> TodoItem {
+        let completed = finished.is_some();
+        let mut contexts = lists;
+        if let Some(tags) = &task.tags {
+            contexts.extend(tags.iter().cloned());
+        }
+
+        TodoItem {
+            completed,
+            priority: if completed { None } else { task.priority.as_ref().and_then(|p| p.chars().next()) },
+            completion_date: finished.map(|d| d.date_naive()),
+            creation_date: Some(task.created.date_naive()),
+            description: task.task_name.to_string(),
+            projects: vec![encode_name(&task.project)],
+            contexts: contexts.iter().map(|c| encode_name(c)).collect(),
+            due: task.remind.as_ref().and_then(|r| r.get_date()),
+        }
+    }
+
+    // The task and the lists it should be added to, the other contexts
+    // become tags
+    fn to_task(&self, project: &str, year: i32, lists: &[&str]) -> Result<(Task, Vec<String>)> {
+        let imported = self.projects.first().map(|p| decode_name(p));
+        let task_project = project::get_import_name(project, imported.as_deref())?;
+
+        let (task_lists, tags): (Vec<String>, Vec<String>) = self.contexts
+            .iter()
+            .map(|c| decode_name(c))
+            .partition(|c| lists.contains(&c.as_str()));
+
+        let mut task = Task::new(&self.description, &task_project, &year);
+        task.priority = self.priority.map(|p| p.to_string());
+        if let Some(created) = self.creation_date.and_then(get_local_date) {
+            task.created = created;
+        }
+
+        if !tags.is_empty() {
+            task.tags = Some(tags);
+        }
+
+        Ok((task, task_lists))
+    }
+
+    pub fn format(&self) -> String {
+        let mut tokens: Vec<String> = Vec::new();
+
+        if self.completed {
+            tokens.push("x".to_string());
+            if let Some(date) = self.completion_date {
+                tokens.push(date.format(DATE_FORMAT).to_string());
+            }
+        } else if let Some(priority) = self.priority {
+            tokens.push(format!("({})", priority));
+        }
+
+        if let Some(date) = self.creation_date {
+            tokens.push(date.format(DATE_FORMAT).to_string());
+        }
+
+        tokens.push(self.description.to_string());
+        tokens.extend(self.projects.iter().map(|p| format!("+{}", p)));
+        tokens.extend(self.contexts.iter().map(|c| format!("@{}", c)));
+
+        if let Some(date) = self.due {
+            tokens.push(format!("due:{}", date.format(DATE_FORMAT)));
+        }
+
+        tokens.join(" ")
+    }
+}
+
+pub fn export(tasks: &[Task]) -> String {
+    tasks.iter()
+        .map(|t| format!("{}\n", TodoItem::from_task(t).format()))
+        .collect()
+}
+
+pub fn import(contents: &str, project: &str) -> Result<Vec<Task>> {
+    let mut imported: Vec<Task> = Vec::new();
+    let year = crate::setting::get_year().unwrap_or_else(|| Local::now().year());
+    let valid_list = crate::setting::get_todo_list();
+    let lists: Vec<&str> = valid_list.split(',').collect();
+
+    for line in contents.lines() {
+        let item = TodoItem::parse(line);
+        if item.description.is_empty() {
+            continue;
+        }
+
+        let (task, task_lists) = item.to_task(project, year, &lists)?;
+        if Task::get_by_id_or_name(&task.task_name, false, &task.project).is_ok() {
+            eprintln!("skipping {}, a task with that name already exists", task.task_name);
+            continue;
+        }
+
+        task.add("")?;
+
+        if item.completed {
+            let finished = item.completion_date.and_then(get_local_date).unwrap_or_else(Local::now);
+            task.finish_at("Task Completed", finished)?;
+            imported.push(task);
+            continue;
+        }
+
+        if let Some(date) = item.due {
+            task.set_reminder(
+                &date.format("%m").to_string(),
+                &date.format("%d").to_string(),
+                &date.format("%Y").to_string(),
+                "",
+                &0
+            )?;
+        }
+
+        for list in task_lists {
+            // A full list should not stop the rest of the import, which
+            // would skip the tasks already created when it is run again
+            if let Err(e) = TaskList::get(&list).add(task.clone()) {
+                eprintln!("imported {} but could not add it to {} with error {}", task.task_name, list, e);
+            }
+        }
+
+        imported.push(task);
+    }
+
+    Ok(imported)
+}
+
+// Projects, lists and tags can not hold spaces in todo.txt, so spaces are
+// written as underscores and the underscores and percent signs already in
+// a name are escaped
+fn encode_name(name: &str) -> String {
+    name.replace('%', "%25").replace('_', "%5F").replace(' ', "_")
+}
+
+fn decode_name(name: &str) -> String {
+    link::decode(&name.replace('_', " "))
+}
+
+fn get_local_date(date: NaiveDate) -> Option<DateTime<Local>> {
+    date.and_hms_opt(0, 0, 0).and_then(|d| Local.from_local_datetime(&d).single())
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT).ok()
+}
+
+fn parse_priority(value: &str) -> Option<char> {
+    let chars: Vec<char> = value.chars().collect();
+    match chars.as_slice() {
+        ['(', p, ')'] if p.is_ascii_uppercase() => Some(*p),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(line: &str) {
+        let item = TodoItem::parse(line);
+        assert_eq!(item.format(), line);
+        assert_eq!(TodoItem::parse(&item.format()), item);
+    }
+
+    #[test]
+    fn round_trips_open_task() {
+        round_trip("(A) 2026-10-01 Call the plumber +Home @Today due:2026-10-25");
+    }
+
+    #[test]
+    fn round_trips_completed_task() {
+        round_trip("x 2026-10-19 2026-10-01 File taxes +Finance @Waiting");
+    }
+
+    #[test]
+    fn round_trips_minimal_task() {
+        round_trip("Buy milk");
+    }
+
+    #[test]
+    fn parses_fields() {
+        let item = TodoItem::parse("(B) 2026-10-01 Write report +Work +Q4 @office due:2026-11-02");
+        assert!(!item.completed);
+        assert_eq!(item.priority, Some('B'));
+        assert_eq!(item.creation_date, NaiveDate::from_ymd_opt(2026, 10, 1));
+        assert_eq!(item.description, "Write report");
+        assert_eq!(item.projects, vec!["Work", "Q4"]);
+        assert_eq!(item.contexts, vec!["office"]);
+        assert_eq!(item.due, NaiveDate::from_ymd_opt(2026, 11, 2));
+    }
+
+    #[test]
+    fn priority_is_not_read_after_completion() {
+        let item = TodoItem::parse("x (A) Done already");
+        assert!(item.completed);
+        assert_eq!(item.priority, None);
+        assert_eq!(item.description, "(A) Done already");
+    }
+
+    #[test]
+    fn round_trips_task() {
+        std::env::set_var("TODUIT_PROJECT_FOLDER_NAME", "Projects");
+        let mut task = Task::new("Fix the fence", "Home Repair", &2026);
+        task.priority = Some("A".to_string());
+        task.tags = Some(vec!["outside work".to_string(), "50%_done".to_string()]);
+
+        let line = TodoItem::from_task_on_lists(&task, vec!["Next Week".to_string()], None).format();
+        let (imported, lists) = TodoItem::parse(&line).to_task("", 2026, &["Today", "Next Week"]).unwrap();
+
+        assert_eq!(imported.task_name, task.task_name);
+        assert_eq!(imported.project, task.project);
+        assert_eq!(imported.path, task.path);
+        assert_eq!(imported.priority, task.priority);
+        assert_eq!(imported.tags, task.tags);
+        assert_eq!(imported.created.date_naive(), task.created.date_naive());
+        assert_eq!(lists, vec!["Next Week"]);
+    }
+
+    #[test]
+    fn exports_finish_date() {
+        std::env::set_var("TODUIT_PROJECT_FOLDER_NAME", "Projects");
+        let task = Task::new("File taxes", "Finance", &2026);
+        let finished = Local.with_ymd_and_hms(2026, 10, 5, 9, 30, 0).unwrap();
+
+        let item = TodoItem::from_task_on_lists(&task, Vec::new(), Some(finished));
+        assert!(item.format().starts_with("x 2026-10-05 "));
+        assert_eq!(TodoItem::parse(&item.format()).completion_date, NaiveDate::from_ymd_opt(2026, 10, 5));
+    }
+
+    #[test]
+    fn decodes_names() {
+        for name in &["Home Repair", "snake_case", "100%", "a _b", "a_ b", "%5F"] {
+            assert!(!encode_name(name).contains(' '));
+            assert_eq!(decode_name(&encode_name(name)), *name);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+extern crate crossterm;
+
+use crossterm::cursor;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::env;
+use std::io::{stdout, Error, ErrorKind, Result, Stdout, Write};
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use crate::task::Task;
+use crate::task_list::{self, TaskList};
+
+const NEW_COLUMN: &str = "New";
+const MIN_COLUMN_WIDTH: usize = 24;
+const HELP: &str = "h/l column  j/k task  1-9 move to list  u unlist  f finish  c cancel  r rename  n note  o open  q quit";
+
+// New tasks are shown per project, listed tasks per list
+struct Column {
+    name: String,
+    list: Option<usize>,
+    tasks: Vec<Task>,
+}
+
+struct App {
+    columns: Vec<Column>,
+    column: usize,
+    row: usize,
+    status: String,
+}
+
+// Restores the terminal even when the app returns early with an error
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new(out: &mut Stdout) -> Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+pub fn run() -> Result<()> {
+    let mut out = stdout();
+    let _guard = TerminalGuard::new(&mut out)?;
+    let mut app = App { columns: Vec::new(), column: 0, row: 0, status: HELP.to_string() };
+    app.load()?;
+
+    loop {
+        app.draw(&mut out)?;
+
+        let key = match event::read()? {
+            Event::Key(k) if k.kind == KeyEventKind::Press => k,
+            _ => continue,
+        };
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Left | KeyCode::Char('h') => app.move_column(-1),
+            KeyCode::Right | KeyCode::Char('l') => app.move_column(1),
+            KeyCode::Up | KeyCode::Char('k') => app.move_row(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.move_row(1),
+            KeyCode::Char(c) => app.run_action(&mut out, c)?,
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+impl App {
+    fn load(&mut self) -> Result<()> {
+        let mut projects: BTreeMap<String, Vec<Task>> = BTreeMap::new();
+        for task in Task::get_all(true, "")? {
+            projects.entry(task.project.to_string()).or_default().push(task);
+        }
+
+        let mut columns: Vec<Column> = projects
+            .into_iter()
+            .map(|(name, tasks)| Column { name, list: None, tasks })
+            .collect();
+
+        if columns.is_empty() {
+            columns.push(Column { name: NEW_COLUMN.to_string(), list: None, tasks: Vec::new() });
+        }
+
+        // A list that can not be read is shown empty, so the others can
+        // still be triaged
+        for (i, name) in task_list::get_names().into_iter().enumerate() {
+            let tasks = TaskList::get(&name).get_tasks().unwrap_or_else(|e| {
+                self.status = format!("Error: could not read {} with error {}", name, e);
+                Vec::new()
+            });
+
+            columns.push(Column { name, list: Some(i + 1), tasks });
+        }
+
+        self.columns = columns;
+        self.column = self.column.min(self.columns.len() - 1);
+        self.move_row(0);
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<&Task> {
+        self.columns.get(self.column)?.tasks.get(self.row)
+    }
+
+    fn move_column(&mut self, delta: i32) {
+        let last = self.columns.len() as i32 - 1;
+        self.column = (self.column as i32 + delta).max(0).min(last) as usize;
+        self.move_row(0);
+    }
+
+    fn move_row(&mut self, delta: i32) {
+        let last = self.columns[self.column].tasks.len() as i32 - 1;
+        self.row = (self.row as i32 + delta).min(last).max(0) as usize;
+    }
+
+    fn run_action(&mut self, out: &mut Stdout, action: char) -> Result<()> {
+        let task = match self.selected() {
+            Some(t) => t.clone(),
+            None => return Ok(()),
+        };
+
+        let input = match action {
+            'r' => match self.prompt(out, "Rename to: ")? {
+                Some(i) => i,
+                None => return Ok(()),
+            },
+            'n' => match self.prompt(out, "Note: ")? {
+                Some(i) => i,
+                None => return Ok(()),
+            },
+            _ => String::new(),
+        };
+
+        if action == 'o' {
+            self.open(out, &task)?;
+            return self.load();
+        }
+
+        self.status = match self.apply(&task, action, &input) {
+            Ok(status) => status,
+            Err(e) => format!("Error: {}", e),
+        };
+
+        self.load()
+    }
+
+    fn apply(&self, task: &Task, action: char, input: &str) -> Result<String> {
+        match action {
+            '1'..='9' => {
+                let index = action.to_digit(10).unwrap_or(0) as usize;
+                let list = self.columns.iter()
+                    .find(|c| c.list == Some(index))
+                    .ok_or_else(|| Error::new(ErrorKind::NotFound, "no such list"))?;
+                TaskList::get(&list.name).add(task.clone())?;
+                Ok(format!("Added {} to {}", task.task_name, list.name))
+            }
+            'u' => {
+                task.unlist()?;
+                Ok(format!("Unlisted {}", task.task_name))
+            }
+            'f' => {
+                task.finish("Task Completed")?;
+                Ok(format!("Finished {}", task.task_name))
+            }
+            'c' => {
+                task.finish("Task Canceled")?;
+                Ok(format!("Canceled {}", task.task_name))
+            }
+            'r' => {
+                task.rename_task(input)?;
+                Ok(format!("Renamed {} to {}", task.task_name, input))
+            }
+            'n' => {
+                task.add_comment(input, task.check_is_new()?)?;
+                Ok(format!("Added note to {}", task.task_name))
+            }
+            _ => Ok(HELP.to_string()),
+        }
+    }
+
+    fn open(&mut self, out: &mut Stdout, task: &Task) -> Result<()> {
+        let editor = env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+
+        execute!(out, cursor::Show, LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+        let status = Command::new(editor).arg(task.get_file_path()).status();
+        terminal::enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, cursor::Hide)?;
+
+        if let Err(e) = status {
+            self.status = format!("Error: {}", e);
+        }
+
+        Ok(())
+    }
+
+    fn prompt(&mut self, out: &mut Stdout, label: &str) -> Result<Option<String>> {
+        let mut input = String::new();
+
+        loop {
+            self.status = format!("{}{}", label, input);
+            self.draw(out)?;
+
+            match event::read()? {
+                Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) => match code {
+                    KeyCode::Enter if !input.trim().is_empty() => return Ok(Some(input.trim().to_string())),
+                    KeyCode::Esc => {
+                        self.status = HELP.to_string();
+                        return Ok(None);
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => (),
+                },
+                _ => continue,
+            }
+        }
+    }
+
+    fn draw(&self, out: &mut Stdout) -> Result<()> {
+        let (width, height) = terminal::size()?;
+        let visible_columns = (width as usize / MIN_COLUMN_WIDTH).clamp(1, self.columns.len());
+        let column_width = (width as usize / visible_columns).max(1);
+        let visible_rows = (height as usize).saturating_sub(3);
+
+        // Columns scroll sideways to keep the selected one in view
+        let first_column = (self.column + 1).saturating_sub(visible_columns);
+
+        queue!(out, terminal::Clear(ClearType::All))?;
+
+        for (i, column) in self.columns.iter().enumerate().skip(first_column).take(visible_columns) {
+            let x = ((i - first_column) * column_width) as u16;
+            let header = match column.list {
+                Some(number) => format!("{}:{} ({})", number, column.name, column.tasks.len()),
+                None => format!("{} ({})", column.name, column.tasks.len()),
+            };
+
+            queue!(
+                out,
+                cursor::MoveTo(x, 0),
+                SetAttribute(if i == self.column { Attribute::Bold } else { Attribute::Dim }),
+                Print(truncate(&header, column_width - 1)),
+                SetAttribute(Attribute::Reset)
+            )?;
+
+            let offset = if i == self.column && self.row >= visible_rows {
+                self.row + 1 - visible_rows
+            } else {
+                0
+            };
+
+            for (r, task) in column.tasks.iter().enumerate().skip(offset).take(visible_rows) {
+                let label = match column.list {
+                    Some(_n) => format!("{} - {}", task.project, task.task_name),
+                    None => task.task_name.to_string(),
+                };
+                let selected = i == self.column && r == self.row;
+
+                queue!(
+                    out,
+                    cursor::MoveTo(x, (r - offset + 2) as u16),
+                    SetAttribute(if selected { Attribute::Reverse } else { Attribute::Reset }),
+                    Print(truncate(&label, column_width - 1)),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            }
+        }
+
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1)),
+            Print(truncate(&self.status, width as usize))
+        )?;
+
+        out.flush()
+    }
+}
+
+fn truncate(value: &str, width: usize) -> String {
+    value.chars().take(width).collect()
+}
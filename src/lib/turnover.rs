@@ -0,0 +1,230 @@
+use std::io::{Error, Result};
+
+use chrono::prelude::*;
+
+use crate::layout;
+use crate::storage;
+use crate::task::Task;
+use crate::util::link;
+
+const STATE_FILE_NAME: &str = ".last-turnover";
+
+pub struct Summary {
+    pub old_year: i32,
+    pub new_year: i32,
+    pub moved: Vec<Task>,
+    pub failed: Vec<(String, Error)>,
+    pub reminders: usize,
+    pub report: String,
+}
+
+// Returns the earlier years that still have open tasks when this year has
+// not been turned over yet. Templates without a year have nothing to move
+pub fn get_pending_years() -> Vec<i32> {
+    let current_year = Local::now().year();
+    if !layout::has_year(&layout::get_template()) || get_last_year() >= Some(current_year) {
+        return Vec::new();
+    }
+
+    let mut years: Vec<i32> = layout::get_task_files()
+        .into_iter()
+        .filter_map(|(_f, l)| l.year)
+        .filter(|y| *y < current_year)
+        .collect();
+
+    years.sort_unstable();
+    years.dedup();
+    years.retain(|y| get_open_task_count(*y) > 0);
+    years
+}
+
+pub fn get_open_task_count(year: i32) -> usize {
+    Task::get_open_files(year).len()
+}
+
+// Remembers that the year has been turned over, or declined, so the offer
+// is only made on the first run of a year. The file is only written when
+// the year changes
+pub fn mark_done(year: i32) -> Result<()> {
+    if get_last_year() >= Some(year) {
+        return Ok(());
+    }
+
+    storage::write(get_state_file(), &format!("{}\n", year))
+}
+
+pub fn run(old_year: i32, new_year: i32) -> Result<Summary> {
+    let mut moved: Vec<Task> = Vec::new();
+    let mut failed: Vec<(String, Error)> = Vec::new();
+    for (file, task) in Task::year_turnover(old_year, new_year)? {
+        match task {
+            Ok(t) => moved.push(t),
+            Err(e) => failed.push((file, e)),
+        }
+    }
+
+    let reminders = update_reminders(&moved)?;
+    let report = write_report(old_year, new_year, &moved, &failed)?;
+
+    if new_year >= Local::now().year() {
+        mark_done(new_year)?;
+    }
+
+    Ok(Summary { old_year, new_year, moved, failed, reminders, report })
+}
+
+fn get_last_year() -> Option<i32> {
+    storage::read_to_string(get_state_file())
+        .ok()
+        .and_then(|y| y.trim().parse::<i32>().ok())
+}
+
+fn get_state_file() -> String {
+    format!("{}/{}", crate::setting::get_project_folder(), STATE_FILE_NAME)
+}
+
+// Reminders add their task to a list by name, which is ambiguous once a
+// task of the same name is left behind in the old year, so the moved
+// tasks are pointed at by id instead
+fn update_reminders(moved: &[Task]) -> Result<usize> {
+    let reminder_file = crate::setting::get_reminder_file();
+    let contents = match storage::read_to_string(&reminder_file) {
+        Ok(c) => c,
+        Err(_e) => return Ok(0),
+    };
+
+    let mut updated = 0;
+    let mut new_contents = contents.to_string();
+    for task in moved {
+        let by_name = format!("RUN (toduit add \"{}\" Today)", task.task_name);
+        let by_id = format!("RUN (toduit add \"{}\" Today)", task.id);
+        let count = new_contents.matches(&by_name).count();
+        if count > 0 {
+            new_contents = new_contents.replace(&by_name, &by_id);
+            updated += count;
+        }
+    }
+
+    if new_contents != contents {
+        storage::write(&reminder_file, &new_contents)?;
+    }
+
+    Ok(updated)
+}
+
+fn write_report(old_year: i32, new_year: i32, moved: &[Task], failed: &[(String, Error)]) -> Result<String> {
+    let report_path = format!(
+        "{}/{}-{} Turnover.md",
+        crate::setting::get_review_folder(),
+        old_year,
+        new_year
+    );
+
+    let mut contents = format!(
+        "# {} to {} Turnover \n\n{} open task(s) carried over \n",
+        old_year,
+        new_year,
+        moved.len()
+    );
+
+    let mut tasks: Vec<&Task> = moved.iter().collect();
+    tasks.sort_by(|a, b| a.project.cmp(&b.project).then_with(|| a.task_name.cmp(&b.task_name)));
+
+    let mut current_project: Option<&str> = None;
+    for task in tasks {
+        if current_project != Some(&task.project) {
+            let count = moved.iter().filter(|t| t.project == task.project).count();
+            contents.push_str(&format!("\n#### {} ({}) \n", task.project, count));
+            current_project = Some(&task.project);
+        }
+
+        let is_new = task.check_is_new().unwrap_or(false);
+        let task_path = task.get_location().with_status(is_new).get_relative_path();
+        contents.push_str(&format!(
+            "* [{}](../../../{}) \n",
+            link::escape_text(&task.task_name),
+            link::encode(&task_path),
+        ));
+    }
+
+    if !failed.is_empty() {
+        contents.push_str("\n## Not carried over \n");
+        for (file, e) in failed {
+            contents.push_str(&format!("* {}: {} \n", file, e));
+        }
+    }
+
+    storage::write(&report_path, &contents)?;
+    Ok(report_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+    use std::fs;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn points_reminders_at_moved_tasks() {
+        let root = Root::new();
+        let bob = root.add_task("Call Bob", "Home");
+        let carol = root.add_task("Call Carol", "Home");
+        let reminders = format!("{}/reminders.rem", root.path);
+        fs::write(&reminders, "REM Oct 20 2026 MSG %\"Call Bob%\" [t()] \n\
+            REM Oct 20 2026 RUN (toduit add \"Call Bob\" Today) & \n\
+            REM Nov 1 RUN (toduit add \"Call Bob\" Today) & \n\
+            REM Nov 2 RUN (toduit add \"Pay rent\" Today) & \n").unwrap();
+
+        assert_eq!(update_reminders(&[bob.clone(), carol]).unwrap(), 2);
+        assert_eq!(fs::read_to_string(&reminders).unwrap(), format!("REM Oct 20 2026 MSG %\"Call Bob%\" [t()] \n\
+            REM Oct 20 2026 RUN (toduit add \"{0}\" Today) & \n\
+            REM Nov 1 RUN (toduit add \"{0}\" Today) & \n\
+            REM Nov 2 RUN (toduit add \"Pay rent\" Today) & \n", bob.id));
+
+        let moved = [bob];
+        assert_eq!(update_reminders(&moved).unwrap(), 0);
+        fs::remove_file(&reminders).unwrap();
+        assert_eq!(update_reminders(&moved).unwrap(), 0);
+    }
+
+    #[test]
+    fn writes_report() {
+        let root = Root::new();
+        let moved = vec![
+            root.add_task("Write report", "Work"),
+            root.add_task("Pay rent", "Home"),
+            root.add_task("Call [Bob]", "Home"),
+        ];
+        let failed = vec![("Projects/2025/Home/new/Lost.md".to_string(), Error::new(ErrorKind::AlreadyExists, "already exists"))];
+
+        let report = write_report(2025, 2026, &moved, &failed).unwrap();
+        assert_eq!(report, format!("{}/Review/2026/10 - October/2025-2026 Turnover.md", root.path));
+        assert_eq!(fs::read_to_string(&report).unwrap(), "# 2025 to 2026 Turnover \n\n\
+            3 open task(s) carried over \n\
+            \n#### Home (2) \n\
+            * [Call \\[Bob\\]](../../../Projects/2026/Home/new/Call%20%5BBob%5D.md) \n\
+            * [Pay rent](../../../Projects/2026/Home/new/Pay%20rent.md) \n\
+            \n#### Work (1) \n\
+            * [Write report](../../../Projects/2026/Work/new/Write%20report.md) \n\
+            \n## Not carried over \n\
+            * Projects/2025/Home/new/Lost.md: already exists \n");
+    }
+
+    #[test]
+    fn marks_the_latest_year_done() {
+        let root = Root::new();
+        let state = format!("{}/Projects/{}", root.path, STATE_FILE_NAME);
+        assert_eq!(get_last_year(), None);
+
+        mark_done(2025).unwrap();
+        assert_eq!(fs::read_to_string(&state).unwrap(), "2025\n");
+
+        mark_done(2024).unwrap();
+        mark_done(2025).unwrap();
+        assert_eq!(get_last_year(), Some(2025));
+
+        mark_done(2026).unwrap();
+        assert_eq!(get_last_year(), Some(2026));
+    }
+}
@@ -1,5 +1,5 @@
 pub mod date_format {
-    use chrono::{DateTime, Local, TimeZone};
+    use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
     use serde::{self, Deserialize, Deserializer, Serializer};
 
     const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
@@ -17,8 +17,113 @@ pub mod date_format {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
+        let naive = NaiveDateTime::parse_from_str(&s, FORMAT)
+            .map_err(serde::de::Error::custom)?;
+
         Local
-            .datetime_from_str(&s, FORMAT)
-            .map_err(serde::de::Error::custom)
+            .from_local_datetime(&naive)
+            .single()
+            .ok_or_else(|| serde::de::Error::custom("ambiguous local date"))
+    }
+}
+
+pub mod optional_date {
+    use chrono::NaiveDate;
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    const FORMAT: &str = "%Y-%m-%d";
+
+    pub fn serialize<S>(date: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(d) => serializer.serialize_str(&d.format(FORMAT).to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| NaiveDate::parse_from_str(&s, FORMAT).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+pub mod link {
+    // Percent-encodes a path for a markdown link, leaving the folder
+    // separators and characters that never need escaping as they are
+    pub fn encode(path: &str) -> String {
+        let mut encoded = String::new();
+        for b in path.bytes() {
+            match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                    encoded.push(b as char)
+                }
+                _ => encoded.push_str(&format!("%{:02X}", b)),
+            }
+        }
+
+        encoded
+    }
+
+    // Invalid escapes are kept as they are, since older links were
+    // written without any encoding
+    pub fn decode(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut decoded: Vec<u8> = Vec::new();
+        let mut i = 0;
+
+        while i < bytes.len() {
+            if bytes[i] == b'%' && i + 2 < bytes.len() {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                // from_str_radix also takes a sign, which is not an escape
+                if let (true, Ok(b)) = (hex.bytes().all(|c| c.is_ascii_hexdigit()), u8::from_str_radix(hex, 16)) {
+                    decoded.push(b);
+                    i += 3;
+                    continue;
+                }
+            }
+
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+
+        String::from_utf8_lossy(&decoded).to_string()
+    }
+
+    pub fn escape_text(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn round_trips_paths() {
+            for path in &["Projects/2026/Home/Fix the fence.md", "Projects/2026/100%/a+b (c)#d?.md", "Projects/2026/Café/naïve.md", ""] {
+                assert_eq!(decode(&encode(path)), *path);
+            }
+        }
+
+        #[test]
+        fn encodes_only_what_needs_it() {
+            assert_eq!(encode("Projects/2026/a-b_c.d~e.md"), "Projects/2026/a-b_c.d~e.md");
+            assert_eq!(encode("a b%(c)"), "a%20b%25%28c%29");
+            assert_eq!(encode("é"), "%C3%A9");
+        }
+
+        #[test]
+        fn keeps_invalid_escapes() {
+            assert_eq!(decode("100%"), "100%");
+            assert_eq!(decode("100%2"), "100%2");
+            assert_eq!(decode("%zz and %+1 and %-1"), "%zz and %+1 and %-1");
+            assert_eq!(decode("a b%20c"), "a b c");
+            assert_eq!(decode("%e9%C3%A9"), "\u{FFFD}é");
+        }
     }
 }
@@ -0,0 +1,124 @@
+use std::io::{Error, ErrorKind, Result};
+
+use chrono::prelude::*;
+use chrono::Duration;
+
+use crate::task::Task;
+use crate::task_list::{TaskList, TODAY_LIST};
+
+pub const WAITING_LIST: &str = "Waiting";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FollowUp {
+    None,
+    Later,
+    Due,
+    Overdue,
+}
+
+// Follow ups are given in days or weeks from today, like `3d` or `2w`, or
+// as a date in the MM:DD:YYYY form reminders use or as YYYY-MM-DD
+pub fn parse_follow_up(value: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let value = value.trim();
+    let invalid = || Error::new(
+        ErrorKind::InvalidInput,
+        format!("{} is not a follow up, use a number of days or weeks like 3d or 2w, or a date", value)
+    );
+
+    let count = |suffix: char| value.strip_suffix(suffix).and_then(|n| n.parse::<i64>().ok());
+    if let Some(days) = count('d') {
+        return Ok(today + Duration::days(days));
+    }
+
+    if let Some(weeks) = count('w') {
+        return Ok(today + Duration::weeks(weeks));
+    }
+
+    NaiveDate::parse_from_str(value, "%m:%d:%Y")
+        .or_else(|_e| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .map_err(|_e| invalid())
+}
+
+pub fn get_follow_up(task: &Task, today: NaiveDate) -> FollowUp {
+    match task.follow_up {
+        Some(date) if date < today => FollowUp::Overdue,
+        Some(date) if date == today => FollowUp::Due,
+        Some(_date) => FollowUp::Later,
+        None => FollowUp::None,
+    }
+}
+
+pub fn get_waiting() -> Result<Vec<Task>> {
+    TaskList::get(WAITING_LIST).get_tasks()
+}
+
+// Moves a task whose follow up has come back to Today, which takes it off
+// the waiting list
+pub fn follow_up(task: &Task, force: bool) -> Result<()> {
+    let today = TaskList::get(TODAY_LIST);
+    match force {
+        true => today.add_over_limit(task.clone())?,
+        false => today.add(task.clone())?,
+    }
+
+    let who = task.waiting_on.as_deref().unwrap_or("someone");
+    Task::get(&task.get_file_path())?.add_comment(&format!("Follow up with {}", who), false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Root;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_follow_ups() {
+        let today = date(2026, 10, 19);
+        let cases = [
+            ("0d", date(2026, 10, 19)),
+            ("3d", date(2026, 10, 22)),
+            ("14d", date(2026, 11, 2)),
+            (" 2w ", date(2026, 11, 2)),
+            ("12w", date(2027, 1, 11)),
+            ("-1d", date(2026, 10, 18)),
+            ("11:05:2026", date(2026, 11, 5)),
+            ("1:5:2027", date(2027, 1, 5)),
+            ("2026-12-24", date(2026, 12, 24)),
+        ];
+
+        for (value, expected) in &cases {
+            assert_eq!(parse_follow_up(value, today).unwrap(), *expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_other_follow_ups() {
+        let today = date(2026, 10, 19);
+        for value in &["", "d", "3", "3m", "two weeks", "2.5w", "13:01:2026", "02:30:2026", "2026-02-30", "10/20/2026"] {
+            let error = parse_follow_up(value, today).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput, "{}", value);
+        }
+    }
+
+    #[test]
+    fn compares_follow_ups_with_today() {
+        let _root = Root::new();
+        let today = date(2026, 10, 19);
+        let mut task = Task::new("Call Bob", "Home", &2026);
+        let cases = [
+            (None, FollowUp::None),
+            (Some(date(2026, 10, 18)), FollowUp::Overdue),
+            (Some(date(2025, 12, 31)), FollowUp::Overdue),
+            (Some(date(2026, 10, 19)), FollowUp::Due),
+            (Some(date(2026, 10, 20)), FollowUp::Later),
+        ];
+
+        for (follow_up, expected) in &cases {
+            task.follow_up = *follow_up;
+            assert_eq!(get_follow_up(&task, today), *expected, "{:?}", follow_up);
+        }
+    }
+}
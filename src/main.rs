@@ -2,29 +2,81 @@ extern crate chrono;
 mod settings;
 
 use chrono::prelude::*;
-use toduitl::journal::*;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io;
+use crossterm::style::Stylize;
+use std::io::{IsTerminal, Read, Write};
+use std::process;
+use toduitl::completion;
+use toduitl::doctor;
+use toduitl::fuzzy;
+use toduitl::layout;
+use toduitl::ics;
+use toduitl::markdown;
+use toduitl::org;
+use toduitl::plan;
+use toduitl::project;
+use toduitl::project::Project;
+use toduitl::server;
 use toduitl::task::*;
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
 use settings::*;
 use toduitl::task_list::*;
+use toduitl::taskwarrior;
+use toduitl::todo_txt;
+use toduitl::turnover;
+use toduitl::waiting;
+use toduitl::waiting::FollowUp;
+use toduitl::tui;
+
+// Subcommand names offered by the shell completion scripts
+const COMMANDS: &[&str] = &[
+    "create", "add", "cancel", "finish", "list", "rename", "plan", "unlist",
+    "change-project", "tag", "untag", "turnover-year", "review", "export", "import",
+    "serve", "tui", "completions", "init", "doctor", "migrate", "project", "lists", "move", "wait", "waiting",
+];
 
 #[derive(StructOpt)]
 struct Cli {
+    #[structopt(long = "exact", global = true, help = "Only match task ids and names exactly")]
+    exact: bool,
+
+    #[structopt(long = "dry-run", global = true, help = "Print the changes a command would make without making them")]
+    dry_run: bool,
+
+    #[structopt(short = "y", long = "year", global = true, help = "Only look at tasks from this year and create new tasks in it")]
+    year: Option<i32>,
+
     #[structopt(subcommand)]
     action: Action,
 }
 
 #[derive(StructOpt)]
 enum Action {
+    Init {
+        #[structopt(long = "root", help = "Folder to keep tasks in, defaults to ~/.local/todo")]
+        root: Option<String>,
+    },
+    Doctor {
+        #[structopt(long = "fix", help = "Repair the issues that can be fixed safely")]
+        fix: bool,
+
+        #[structopt(long = "trim", help = "Remove the leftover text shown for this task file")]
+        trim: Vec<String>,
+    },
+    Migrate {
+        #[structopt(long = "from", default_value = layout::DEFAULT_TEMPLATE, help = "Path template the tasks are laid out with now")]
+        from: String,
+    },
     Create {
         task_name: String,
 
         #[structopt(default_value = "")]
         description: String,
 
-        #[structopt(short = "y", long = "year", default_value = "")]
-        year: String,
-
         #[structopt(short = "d", long = "date", default_value = "", help = "MM:DD:YYYY")]
         date: String,
 
@@ -36,22 +88,43 @@ enum Action {
 
         #[structopt(short = "p", long = "project", default_value = "Inbox")]
         project: String,
+
+        #[structopt(long = "force", help = "Add the task to the project's list even when it is at its limit")]
+        force: bool,
     },
     Add {
-        task_name: String,
-        list_name: String,
+        #[structopt(
+            value_name = "TASK... LIST",
+            required = true,
+            help = "Task names or ids followed by the list name, - reads tasks from stdin"
+        )]
+        targets: Vec<String>,
+
+        #[structopt(short = "q", long = "query", default_value = "")]
+        query: String,
 
         #[structopt(short = "p", long = "project", default_value = "")]
         project: String,
+
+        #[structopt(long = "force", help = "Add the tasks even when the list is at its limit")]
+        force: bool,
     },
     Cancel {
-        task_name: String,
+        #[structopt(help = "Task names or ids, - reads them from stdin")]
+        task_names: Vec<String>,
+
+        #[structopt(short = "q", long = "query", default_value = "")]
+        query: String,
 
         #[structopt(short = "p", long = "project", default_value = "")]
         project: String,
     },
     Finish {
-        task_name: String,
+        #[structopt(help = "Task names or ids, - reads them from stdin")]
+        task_names: Vec<String>,
+
+        #[structopt(short = "q", long = "query", default_value = "")]
+        query: String,
 
         #[structopt(short = "p", long = "project", default_value = "")]
         project: String,
@@ -66,45 +139,355 @@ enum Action {
         #[structopt(short = "p", long = "project", default_value = "")]
         project: String,
     },
-    AddJournal, 
+    Plan {
+        #[structopt(long = "force", help = "Add the accepted tasks even when Today is at its limit")]
+        force: bool,
+    },
     Unlist {
-        task_name: String,
+        #[structopt(help = "Task names or ids, - reads them from stdin")]
+        task_names: Vec<String>,
+
+        #[structopt(short = "q", long = "query", default_value = "")]
+        query: String,
 
         #[structopt(short = "p", long = "project", default_value = "")]
         project: String,
     },
     ChangeProject {
-        task: String,
-        new_project: String,
-        
+        #[structopt(
+            value_name = "TASK... PROJECT",
+            required = true,
+            help = "Task names or ids followed by the new project, - reads tasks from stdin"
+        )]
+        targets: Vec<String>,
+
+        #[structopt(short = "q", long = "query", default_value = "")]
+        query: String,
+
+        #[structopt(short = "p", long = "project", default_value = "")]
+        project: String,
+    },
+    Tag {
+        tag: String,
+
+        #[structopt(help = "Task names or ids, - reads them from stdin")]
+        task_names: Vec<String>,
+
+        #[structopt(short = "q", long = "query", default_value = "")]
+        query: String,
+
+        #[structopt(short = "p", long = "project", default_value = "")]
+        project: String,
+    },
+    Untag {
+        tag: String,
+
+        #[structopt(help = "Task names or ids, - reads them from stdin")]
+        task_names: Vec<String>,
+
+        #[structopt(short = "q", long = "query", default_value = "")]
+        query: String,
+
         #[structopt(short = "p", long = "project", default_value = "")]
         project: String,
     },
     TurnoverYear {
-        old_year: String,
-        new_year: String,
+        old_year: i32,
+        new_year: i32,
     },
     Review {
         #[structopt(short = "p", long = "project", default_value = "")]
         project: String,
-    }
+    },
+    Project {
+        #[structopt(subcommand)]
+        command: ProjectCommand,
+    },
+    Lists {
+        #[structopt(subcommand)]
+        command: Option<ListsCommand>,
+    },
+    Wait {
+        #[structopt(help = "Task name, id or list position like Today:2")]
+        task_name: String,
+
+        #[structopt(long = "on", help = "Who or what the task is waiting on")]
+        on: String,
+
+        #[structopt(long = "followup", help = "When to follow up, like 3d, 2w or MM:DD:YYYY")]
+        follow_up: Option<String>,
+
+        #[structopt(long = "force", help = "Add the task to Waiting even when it is at its limit")]
+        force: bool,
+    },
+    Waiting {
+        #[structopt(long = "today", help = "Add the tasks that are due a follow up to Today")]
+        today: bool,
+
+        #[structopt(long = "force", requires = "today", help = "Add the tasks even when Today is at its limit")]
+        force: bool,
+    },
+    Move {
+        #[structopt(help = "Task name, id or list position like Today:2")]
+        task_name: String,
+
+        #[structopt(long = "top", conflicts_with_all = &["bottom", "before", "after"])]
+        top: bool,
+
+        #[structopt(long = "bottom", conflicts_with_all = &["before", "after"])]
+        bottom: bool,
+
+        #[structopt(long = "before", conflicts_with = "after", help = "Task or position on the same list")]
+        before: Option<String>,
+
+        #[structopt(long = "after", help = "Task or position on the same list")]
+        after: Option<String>,
+    },
+    Export {
+        #[structopt(subcommand)]
+        format: ExportFormat,
+    },
+    Import {
+        #[structopt(subcommand)]
+        format: ImportFormat,
+    },
+    Serve {
+        #[structopt(short = "b", long = "bind", default_value = "127.0.0.1:8080")]
+        bind: String,
+    },
+    Tui,
+    Completions {
+        #[structopt(possible_values = &["bash", "zsh", "fish"])]
+        shell: String,
+    },
+    #[structopt(name = "__complete", setting = AppSettings::Hidden)]
+    Complete {
+        kind: String,
+
+        #[structopt(default_value = "")]
+        prefix: String,
+    },
+}
+
+#[derive(StructOpt)]
+enum ImportFormat {
+    Ics {
+        file: String,
+
+        #[structopt(short = "p", long = "project", default_value = "")]
+        project: String,
+    },
+    Todotxt {
+        #[structopt(default_value = "-")]
+        file: String,
+
+        #[structopt(short = "p", long = "project", default_value = "")]
+        project: String,
+    },
+    Taskwarrior {
+        #[structopt(default_value = "-")]
+        file: String,
+
+        #[structopt(short = "p", long = "project", default_value = "")]
+        project: String,
+    },
+    Markdown {
+        file: String,
+
+        #[structopt(short = "p", long = "project", default_value = "Inbox")]
+        project: String,
+
+        #[structopt(short = "l", long = "list", default_value = "")]
+        list: String,
+    },
+}
+
+#[derive(StructOpt)]
+enum ProjectCommand {
+    List,
+    Create {
+        project: String,
+
+        #[structopt(long = "description")]
+        description: Option<String>,
+
+        #[structopt(long = "owner")]
+        owner: Option<String>,
+
+        #[structopt(short = "l", long = "list", help = "List new tasks in the project are added to")]
+        list: Option<String>,
+
+        #[structopt(long = "color")]
+        color: Option<String>,
+    },
+    Rename {
+        project: String,
+        new_name: String,
+    },
+    Archive {
+        project: String,
+    },
+}
+
+#[derive(StructOpt)]
+enum ListsCommand {
+    Create {
+        list_name: String,
+    },
+    Delete {
+        list_name: String,
+
+        #[structopt(long = "force", help = "Unlist the tasks on the list and delete it")]
+        force: bool,
+    },
+    Rename {
+        list_name: String,
+        new_name: String,
+    },
+    Reorder {
+        #[structopt(required = true, help = "Every list in the new order")]
+        list_names: Vec<String>,
+    },
+    Limit {
+        list_name: String,
+
+        #[structopt(help = "Most tasks the list can have, 0 removes the limit")]
+        limit: usize,
+    },
+}
+
+#[derive(StructOpt)]
+enum ExportFormat {
+    Ics {
+        #[structopt(short = "p", long = "project", default_value = "")]
+        project: String,
+    },
+    Todotxt {
+        #[structopt(short = "p", long = "project", default_value = "")]
+        project: String,
+    },
+    Taskwarrior {
+        #[structopt(short = "p", long = "project", default_value = "")]
+        project: String,
+    },
+    Org {
+        #[structopt(short = "p", long = "project", conflicts_with = "list")]
+        project: Option<String>,
+
+        #[structopt(short = "l", long = "list")]
+        list: Option<String>,
+    },
 }
 
 fn main() {
     let args = Cli::from_args();
-    Settings::new();
+    let exact = args.exact;
+
+    // Loading the settings can write files, so a dry run is set up first
+    if args.dry_run {
+        env::set_var("TODUIT_DRY_RUN", "1");
+    }
+
+    // Init creates the settings, so they are not loaded beforehand
+    if !matches!(args.action, Action::Init { .. }) {
+        Settings::new();
+    }
+
+    if let Some(year) = args.year {
+        env::set_var("TODUIT_YEAR", year.to_string());
+    }
+
+    let offers_turnover = !matches!(
+        args.action,
+        Action::Init { .. } | Action::Doctor { .. } | Action::Migrate { .. } | Action::TurnoverYear { .. }
+            | Action::Serve { .. } | Action::Completions { .. } | Action::Complete { .. }
+    );
+
+    if offers_turnover {
+        offer_turnover();
+    }
 
     match args.action {
+        Action::Init {
+            root
+        } => {
+            if let Err(e) = init(root.as_deref()) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        Action::Doctor {
+            fix,
+            trim
+        } => {
+            let issues = doctor::check().expect("could not check the task folders");
+            let mut remaining = 0;
+
+            for issue in &issues {
+                // Leftover text can look like a real note, so it is only
+                // removed from the files named after checking it
+                let is_trimmed = trim.iter().any(|t| is_same_file(t, &issue.file));
+                let should_fix = match issue.kind {
+                    doctor::IssueKind::TrailingGarbage => is_trimmed,
+                    _ => fix && issue.fixable,
+                };
+
+                let status = if !should_fix {
+                    remaining += 1;
+                    match issue.kind {
+                        doctor::IssueKind::TrailingGarbage => "check it, then use --trim with this file",
+                        _ if issue.fixable => "fixable",
+                        _ => "not fixable",
+                    }
+                } else {
+                    match doctor::fix(issue) {
+                        Ok(_) => "fixed",
+                        Err(e) => {
+                            remaining += 1;
+                            eprintln!("could not fix {} with error {}", issue.file, e);
+                            "not fixed"
+                        }
+                    }
+                };
+
+                println!("{}: {} ({})", issue.file, issue.message, status);
+            }
+
+            println!("{} issue(s) found, {} fixed", issues.len(), issues.len() - remaining);
+            if remaining > 0 {
+                process::exit(1);
+            }
+        }
+        Action::Migrate {
+            from
+        } => {
+            let moved = Task::migrate_layout(&from).expect("could not migrate tasks");
+            if !toduitl::setting::is_dry_run() {
+                for (old_path, new_path) in &moved {
+                    println!("moved {} to {}", old_path, new_path);
+                }
+            }
+
+            println!("{} task(s) moved to {}", moved.len(), layout::get_template());
+        }
         Action::Create {
             task_name,
             description,
-            year,
             date,
             time,
             notice,
-            project
+            project,
+            force
         } => {
-            let project_year = get_project_year(&year);
+            let project = match project::normalize_name(&project) {
+                Ok(p) => p,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            };
+
+            let project_year = args.year.unwrap_or_else(|| Local::now().year());
             let task = Task::new(
                 &task_name,
                 &project,
@@ -113,13 +496,13 @@ fn main() {
 
             task.add(&description).expect("could not add task");
 
-            if date != "" || time != "" {
+            if !date.is_empty() || !time.is_empty() {
                 let mut month  = "";
                 let mut day = "";
                 let mut ryear = "";
                 let mut rtime = "";
          
-                if date != "" {
+                if !date.is_empty() {
                     let date_val = date.split(":").collect::<Vec<&str>>();
                     if date_val[0] != "00" {
                         month = date_val[0];
@@ -132,100 +515,292 @@ fn main() {
                     }
                 }
 
-                if time != "" {
+                if !time.is_empty() {
                     rtime = &time;
                 }
 
-                task.set_reminder(&month, &day, &ryear, &rtime, &notice).
+                task.set_reminder(month, day, ryear, rtime, &notice).
                     expect("could not set reminder");
             }
+
+            if let Some(list_name) = Project::get(&project).ok().and_then(|p| p.default_list) {
+                let list = TaskList::get(&list_name);
+                let added = match force {
+                    true => list.add_over_limit(task.clone()),
+                    false => list.add(task.clone()),
+                };
+
+                if let Err(e) = added {
+                    eprintln!("created {} but could not add it to {} with error {}", task.task_name, list_name, e);
+                    process::exit(1);
+                }
+            }
         }
         Action::List { list_name } => {
             let task_list = TaskList::get(&list_name);
-            let tasks = task_list.get_tasks()
-                .expect("could not get task list");
+            let tasks = match task_list.get_tasks() {
+                Ok(t) => t,
+                Err(e) => {
+                    eprintln!("could not get list {} with error {}", list_name, e);
+                    process::exit(1);
+                }
+            };
 
-            for task in tasks {
-                println!("{} - {}", task.project, task.task_name);
+            for (i, task) in tasks.iter().enumerate() {
+                println!("{}. {} - {}", i + 1, task.project, task.task_name);
             }
 
         }
-        Action::Add {
+        Action::Wait {
             task_name,
-            list_name,
-            project
+            on,
+            follow_up,
+            force
         } => {
-            let task = Task::get_by_id_or_name(&task_name, false, &project)
-                .expect("could not find task");
+            let today = Local::now().date_naive();
+            let follow_up = follow_up.map(|f| waiting::parse_follow_up(&f, today)).transpose();
+            let result = follow_up.and_then(|f| {
+                let task = find_task(&task_name, false, "", exact)?;
+                task.wait(&on, f, force)
+            });
+
+            if let Err(e) = result {
+                eprintln!("could not wait on {} with error {}", task_name, e);
+                process::exit(1);
+            }
+        }
+        Action::Waiting {
+            today,
+            force
+        } => {
+            let date = Local::now().date_naive();
+            let tasks = waiting::get_waiting().unwrap_or_else(|e| {
+                eprintln!("could not get waiting tasks with error {}", e);
+                process::exit(1);
+            });
+            let is_terminal = io::stdout().is_terminal();
+
+            for (i, task) in tasks.iter().enumerate() {
+                let follow_up = waiting::get_follow_up(task, date);
+                let mut line = format!("{}. {} - {}", i + 1, task.project, task.task_name);
+                if let Some(who) = &task.waiting_on {
+                    line.push_str(&format!(", waiting on {}", who));
+                }
+
+                if let Some(follow_up_date) = task.follow_up {
+                    line.push_str(&format!(", follow up {}", follow_up_date.format("%m/%d/%Y")));
+                }
+
+                match follow_up {
+                    FollowUp::Overdue => line.push_str(" (overdue)"),
+                    FollowUp::Due => line.push_str(" (due today)"),
+                    _ => (),
+                }
+
+                match follow_up {
+                    FollowUp::Overdue | FollowUp::Due if is_terminal => println!("{}", line.red().bold()),
+                    _ => println!("{}", line),
+                }
+            }
+
+            if today {
+                let due: Vec<(String, io::Result<Task>)> = tasks
+                    .into_iter()
+                    .filter(|t| matches!(waiting::get_follow_up(t, date), FollowUp::Overdue | FollowUp::Due))
+                    .map(|t| (t.task_name.to_string(), Ok(t)))
+                    .collect();
+
+                if due.is_empty() {
+                    println!("no follow ups are due");
+                } else {
+                    run_batch("added to Today", due, |t| waiting::follow_up(t, force));
+                }
+            }
+        }
+        Action::Move {
+            task_name,
+            top,
+            bottom,
+            before,
+            after
+        } => {
+            let task = match find_task(&task_name, false, "", exact) {
+                Ok(t) => t,
+                Err(e) => {
+                    eprintln!("could not find {}: {}", task_name, e);
+                    process::exit(1);
+                }
+            };
+
+            let list_name = match get_lists_for_task(&task.task_name).pop() {
+                Some(l) => l,
+                None => {
+                    eprintln!("{} is not on a list", task.task_name);
+                    process::exit(1);
+                }
+            };
+
+            let list = TaskList::get(&list_name);
+            let find_other = |other: &str| {
+                let found = match other.parse::<usize>() {
+                    Ok(position) => list.get_task_at(position),
+                    Err(_) => find_task(other, false, "", exact),
+                };
+
+                found.unwrap_or_else(|e| {
+                    eprintln!("could not find {}: {}", other, e);
+                    process::exit(1);
+                })
+            };
+
+            let position = match (before, after) {
+                (Some(other), _) => Position::Before(find_other(&other)),
+                (_, Some(other)) => Position::After(find_other(&other)),
+                _ if bottom => Position::Bottom,
+                _ if top => Position::Top,
+                _ => {
+                    eprintln!("use --top, --bottom, --before or --after to say where to move the task");
+                    process::exit(1);
+                }
+            };
+
+            if let Err(e) = list.move_task(&task, position) {
+                eprintln!("could not move {} with error {}", task.task_name, e);
+                process::exit(1);
+            }
+        }
+        Action::Add {
+            mut targets,
+            query,
+            project,
+            force
+        } => {
+            let list_name = targets.pop().unwrap_or_default();
+            let task_names = targets;
             let list = TaskList::get(&list_name);
-            list.add(task).expect("could not list task");
+            let tasks = resolve_tasks(&task_names, &query, false, &project, exact);
+            run_batch(&format!("added to {}", list_name), tasks, |task| match force {
+                true => list.add_over_limit(task.clone()),
+                false => list.add(task.clone()),
+            });
         }
         Action::Cancel {
-            task_name,
+            task_names,
+            query,
             project
         } => {
-            let task = Task::get_by_id_or_name(&task_name, false, &project)
-                .expect("could not find task");
-            
-            task.finish("Task Canceled").expect("could not cancel");
+            let tasks = resolve_tasks(&task_names, &query, false, &project, exact);
+            run_batch("canceled", tasks, |task| task.finish("Task Canceled"));
         }
         Action::Finish {
-            task_name,
+            task_names,
+            query,
             project
         } => {
-            let task = Task::get_by_id_or_name(&task_name, false, &project)
-                .expect("could not find task");
-            
-            task.finish("Task Completed").expect("could not finish");
+            let tasks = resolve_tasks(&task_names, &query, false, &project, exact);
+            run_batch("finished", tasks, |task| task.finish("Task Completed"));
         }
         Action::Rename {
             task_name,
             new_name,
             project
         } => {
-            let task = Task::get_by_id_or_name(&task_name, false, &project)
+            let task = find_task(&task_name, false, &project, exact)
                 .expect("could not find task");
 
-            Task::rename_task(&task, &new_name).expect("could not rename task");
+            if let Err(e) = Task::rename_task(&task, &new_name) {
+                eprintln!("could not rename {} with error {}", task.task_name, e);
+                process::exit(1);
+            }
         }
-        Action::AddJournal => {
-            let journal = Journal::new("Journal", "My Thoughts Today").expect("could not find journal path");
+        Action::Plan {
+            force
+        } => {
+            let candidates = plan::get_candidates(Local::now().date_naive()).unwrap_or_else(|e| {
+                eprintln!("could not get tasks to plan with error {}", e);
+                process::exit(1);
+            });
 
-            if journal.create().unwrap() {
-                let task_list = TaskList::get("Today");
-                let tasks = task_list.get_tasks()
-                    .expect("could not get task list");
+            if candidates.is_empty() {
+                println!("nothing to plan");
+            }
+
+            let decisions = ask_plan(candidates).unwrap_or_else(|e| {
+                eprintln!("could not read answer with error {}", e);
+                process::exit(1);
+            });
+
+            let results = plan::write(decisions, force).unwrap_or_else(|e| {
+                eprintln!("could not write plan with error {}", e);
+                process::exit(1);
+            });
 
-                journal.add_tasks_to_journal(tasks);
+            let mut failed = 0;
+            for (name, result) in results {
+                if let Err(e) = result {
+                    failed += 1;
+                    eprintln!("failed {}: {}", name, e);
+                }
+            }
+
+            match TaskList::get(TODAY_LIST).get_tasks() {
+                Ok(tasks) => {
+                    let names: Vec<String> = tasks.iter().map(|t| t.task_name.to_string()).collect();
+                    println!("Today has {} task(s): {}", names.len(), names.join(", "));
+                }
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("could not get Today with error {}", e);
+                }
+            }
+
+            if failed > 0 {
+                process::exit(1);
             }
         }
         Action::Unlist {
-            task_name,
+            task_names,
+            query,
             project
         }=> {
-            let task = Task::get_by_id_or_name(&task_name, false, &project)
-                .expect("could not find task");
-
-            Task::add_comment(&task, "Unlisted", false).expect("could not add comment");
-            Task::move_to_new_folder(&task);
-            toduitl::task_list::remove_from_lists(&task_name, "");
+            let tasks = resolve_tasks(&task_names, &query, false, &project, exact);
+            run_batch("unlisted", tasks, |task| task.unlist());
         }
         Action::ChangeProject {
-            task,
-            new_project,
+            mut targets,
+            query,
             project
         } => {
-            let c_task = Task::get_by_id_or_name(&task, true, &project)
-                .expect("the task is either already complete or cannot be found");
-
-            Task::change_project(&c_task, &new_project)
-                .expect("could not change the project path");
+            let new_project = targets.pop().unwrap_or_default();
+            let c_tasks = resolve_tasks(&targets, &query, true, &project, exact);
+            run_batch(&format!("moved to {}", new_project), c_tasks, |task| task.change_project(&new_project));
+        }
+        Action::Tag {
+            tag,
+            task_names,
+            query,
+            project
+        } => {
+            let tasks = resolve_tasks(&task_names, &query, false, &project, exact);
+            run_batch(&format!("tagged {}", tag), tasks, |task| task.add_tag(&tag));
+        }
+        Action::Untag {
+            tag,
+            task_names,
+            query,
+            project
+        } => {
+            let tasks = resolve_tasks(&task_names, &query, false, &project, exact);
+            run_batch(&format!("untagged {}", tag), tasks, |task| task.remove_tag(&tag));
         }
         Action::TurnoverYear {
             old_year,
             new_year,
         } => {
-            Task::year_turnover(&old_year, &new_year).expect("year turnover failed");
+            let summary = turnover::run(old_year, new_year).expect("year turnover failed");
+            if !print_turnover(&summary) {
+                process::exit(1);
+            }
         }
         Action::Review {
             project
@@ -233,14 +808,591 @@ fn main() {
             let tasks = Task::get_all(true, &project).unwrap();
             Task::create_review(tasks).expect("could not create review file");
         }
+        Action::Project {
+            command
+        } => {
+            match command {
+                ProjectCommand::List => {
+                    for count in project::get_counts().expect("could not get projects") {
+                        let archived = if count.archived { " (archived)" } else { "" };
+                        println!("{} - {} open, {} done{}", count.name, count.open, count.done, archived);
+                    }
+                }
+                ProjectCommand::Create { project, description, owner, list, color } => {
+                    let new_project = Project { description, owner, default_list: list, color, ..Project::new(&project) };
+                    if let Err(e) = new_project.create() {
+                        eprintln!("could not create project {} with error {}", project, e);
+                        process::exit(1);
+                    }
+                }
+                ProjectCommand::Rename { project, new_name } => {
+                    match Project::rename(&project, &new_name) {
+                        Ok(moved) => println!("{} task(s) moved from {} to {}", moved, project, new_name),
+                        Err(e) => {
+                            eprintln!("could not rename project {} with error {}", project, e);
+                            process::exit(1);
+                        }
+                    }
+                }
+                ProjectCommand::Archive { project } => {
+                    match Project::archive(&project) {
+                        Ok(moved) => println!("{} task(s) archived from {}", moved, project),
+                        Err(e) => {
+                            eprintln!("could not archive project {} with error {}", project, e);
+                            process::exit(1);
+                        }
+                    }
+                }
+            }
+        }
+        Action::Lists {
+            command
+        } => {
+            let renamed = match &command {
+                Some(ListsCommand::Rename { list_name, new_name }) => Some((list_name.to_string(), new_name.to_string())),
+                _ => None,
+            };
+
+            let names = match command {
+                None => {
+                    for name in toduitl::task_list::get_names() {
+                        let list = TaskList::get(&name);
+                        let count = list.get_tasks().map(|t| t.len()).unwrap_or(0);
+                        match list.get_limit() {
+                            Some(limit) if count > limit => println!("{} - {}/{} task(s), over the limit", name, count, limit),
+                            Some(limit) => println!("{} - {}/{} task(s)", name, count, limit),
+                            None => println!("{} - {} task(s)", name, count),
+                        }
+                    }
+
+                    return;
+                }
+                Some(ListsCommand::Limit { list_name, limit }) => {
+                    if let Err(e) = TaskList::get(&list_name).check() {
+                        eprintln!("{}", e);
+                        process::exit(1);
+                    }
+
+                    let mut limits: Vec<(String, usize)> = toduitl::setting::get_list_limits()
+                        .into_iter()
+                        .filter(|(l, _n)| *l != list_name)
+                        .collect();
+                    if limit > 0 {
+                        limits.push((list_name.to_string(), limit));
+                    }
+
+                    if let Err(e) = save_list_limits(&limits) {
+                        eprintln!("could not save list limits with error {}", e);
+                        process::exit(1);
+                    }
+
+                    return;
+                }
+                Some(ListsCommand::Create { list_name }) => create_list(&list_name),
+                Some(ListsCommand::Delete { list_name, force }) => delete_list(&list_name, force),
+                Some(ListsCommand::Rename { list_name, new_name }) => rename_list(&list_name, &new_name),
+                Some(ListsCommand::Reorder { list_names }) => reorder_lists(&list_names),
+            };
+
+            // Limits follow their list when it is renamed or deleted
+            let names = names.and_then(|n| {
+                let limits: Vec<(String, usize)> = toduitl::setting::get_list_limits()
+                    .into_iter()
+                    .filter_map(|(l, limit)| match &renamed {
+                        Some((from, to)) if l == *from => Some((to.to_string(), limit)),
+                        _ if n.contains(&l) => Some((l, limit)),
+                        _ => None,
+                    })
+                    .collect();
+
+                save_list_limits(&limits)?;
+                Ok(n)
+            });
+
+            match names.and_then(|n| save_setting("todo-lists", &n.join(","))) {
+                Ok(_) => println!("lists are {}", toduitl::task_list::get_names().join(", ")),
+                Err(e) => {
+                    eprintln!("could not change lists with error {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        Action::Export {
+            format
+        } => {
+            match format {
+                ExportFormat::Ics { project } => {
+                    let tasks = Task::get_all(false, &project)
+                        .expect("could not get tasks");
+
+                    print!("{}", ics::export(&tasks));
+                }
+                ExportFormat::Todotxt { project } => {
+                    let tasks = Task::get_all(false, &project)
+                        .expect("could not get tasks");
+
+                    print!("{}", todo_txt::export(&tasks));
+                }
+                ExportFormat::Taskwarrior { project } => {
+                    let tasks = Task::get_all(false, &project)
+                        .expect("could not get tasks");
+
+                    println!("{}", taskwarrior::export(&tasks).expect("could not export tasks"));
+                }
+                ExportFormat::Org { project, list } => {
+                    let (tasks, title) = match (list, project) {
+                        (Some(list), _) => {
+                            let tasks = TaskList::get(&list).get_tasks()
+                                .expect("could not get task list");
+                            (tasks, list)
+                        }
+                        (None, project) => {
+                            let project = project.unwrap_or_default();
+                            let tasks = Task::get_all(false, &project)
+                                .expect("could not get tasks");
+                            (tasks, if project.is_empty() { "Projects".to_string() } else { project })
+                        }
+                    };
+
+                    print!("{}", org::export(&tasks, &title).expect("could not export tasks"));
+                }
+            }
+        }
+        Action::Import {
+            format
+        } => {
+            match format {
+                ImportFormat::Ics { file, project } => {
+                    let contents = read_input(&file)
+                        .expect("could not read import file");
+                    let tasks = ics::import(&contents, &project)
+                        .expect("could not import tasks");
+
+                    for task in tasks {
+                        println!("{} - {}", task.project, task.task_name);
+                    }
+                }
+                ImportFormat::Todotxt { file, project } => {
+                    let contents = read_input(&file)
+                        .expect("could not read import file");
+                    let tasks = todo_txt::import(&contents, &project)
+                        .expect("could not import tasks");
+
+                    for task in tasks {
+                        println!("{} - {}", task.project, task.task_name);
+                    }
+                }
+                ImportFormat::Taskwarrior { file, project } => {
+                    let contents = read_input(&file)
+                        .expect("could not read import file");
+                    let tasks = taskwarrior::import(&contents, &project)
+                        .expect("could not import tasks");
+
+                    for task in tasks {
+                        println!("{} - {}", task.project, task.task_name);
+                    }
+                }
+                ImportFormat::Markdown { file, project, list } => {
+                    let tasks = markdown::import(&file, &project, &list)
+                        .expect("could not import tasks");
+
+                    for task in tasks {
+                        println!("{} - {}", task.project, task.task_name);
+                    }
+                }
+            }
+        }
+        Action::Serve {
+            bind
+        } => {
+            let token = toduitl::setting::get_api_token();
+            server::serve(&bind, &token).expect("could not start server");
+        }
+        Action::Tui => {
+            if let Err(e) = tui::run() {
+                eprintln!("could not run the terminal ui with error {}", e);
+                process::exit(1);
+            }
+        }
+        Action::Completions {
+            shell
+        } => {
+            print!("{}", completion::get_script(&shell).expect("could not generate completions"));
+        }
+        Action::Complete {
+            kind,
+            prefix
+        } => {
+            let candidates = if kind == "commands" {
+                let mut commands: Vec<String> = COMMANDS.iter().map(|c| c.to_string()).collect();
+                completion::filter(&mut commands, &prefix)
+            } else {
+                completion::get_candidates(&kind, &prefix).unwrap_or_default()
+            };
+
+            for candidate in candidates {
+                println!("{}", candidate);
+            }
+        }
+    }
+}
+
+// The first run of a new year offers to carry the open tasks of earlier
+// years over. Scripts and dry runs are never asked, so they are asked again
+// on the next run from a terminal
+fn offer_turnover() {
+    if toduitl::setting::is_dry_run() || !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return;
+    }
+
+    let new_year = Local::now().year();
+    let old_years = turnover::get_pending_years();
+    if old_years.is_empty() {
+        if let Err(e) = turnover::mark_done(new_year) {
+            eprintln!("could not save the turnover year with error {}", e);
+        }
+
+        return;
+    }
+
+    let count: usize = old_years.iter().map(|y| turnover::get_open_task_count(*y)).sum();
+    let years: Vec<String> = old_years.iter().map(|y| y.to_string()).collect();
+    print!("{} open task(s) are still filed under {}, carry them over to {}? [Y/n] ", count, years.join(", "), new_year);
+
+    // Without an answer nothing is saved, so the offer is made again
+    let mut input = String::new();
+    match io::stdout().flush().and_then(|_| io::stdin().read_line(&mut input)) {
+        Ok(0) => {
+            println!();
+            return;
+        }
+        Ok(_n) => (),
+        Err(e) => {
+            eprintln!("could not ask about the year turnover with error {}", e);
+            return;
+        }
+    }
+
+    if !["", "y", "yes"].contains(&input.trim().to_lowercase().as_str()) {
+        println!("not carried over, run `toduit turnover-year <old-year> {}` to do it later", new_year);
+        if let Err(e) = turnover::mark_done(new_year) {
+            eprintln!("could not save the turnover year with error {}", e);
+        }
+
+        return;
+    }
+
+    for old_year in old_years {
+        match turnover::run(old_year, new_year) {
+            Ok(summary) => {
+                print_turnover(&summary);
+            }
+            Err(e) => eprintln!("could not carry over {} with error {}", old_year, e),
+        }
+    }
+}
+
+// Asks about each task, accepting with y and deferring with n. Tasks
+// already on Today are kept unless deferred, the others are only added
+// when accepted. q, or the end of the input, leaves the rest as they are
+// and anything else is asked again
+fn ask_plan(candidates: Vec<plan::Candidate>) -> io::Result<Vec<(plan::Candidate, bool)>> {
+    let mut decisions: Vec<(plan::Candidate, bool)> = Vec::new();
+
+    'candidates: for candidate in candidates {
+        let (reason, default) = match candidate.reason {
+            plan::Reason::Today => ("still on Today".to_string(), true),
+            plan::Reason::Reminder => ("reminder today".to_string(), true),
+            plan::Reason::FollowUp => (
+                format!("follow up with {}", candidate.task.waiting_on.as_deref().unwrap_or("someone")),
+                true
+            ),
+            plan::Reason::Queued => ("queued".to_string(), false),
+        };
+
+        let accepted = loop {
+            print!(
+                "{} - {} ({}) {} ",
+                candidate.task.project,
+                candidate.task.task_name,
+                reason,
+                if default { "[Y/n/q]" } else { "[y/N/q]" }
+            );
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            if io::stdin().read_line(&mut input)? == 0 {
+                println!();
+                break 'candidates;
+            }
+
+            match input.trim().to_lowercase().as_str() {
+                "" => break default,
+                "y" | "yes" => break true,
+                "n" | "no" => break false,
+                "q" | "quit" => break 'candidates,
+                _ => println!("answer y to accept, n to defer or q to leave the rest"),
+            }
+        };
+
+        decisions.push((candidate, accepted));
+    }
+
+    Ok(decisions)
+}
+
+fn print_turnover(summary: &turnover::Summary) -> bool {
+    for (file, e) in &summary.failed {
+        eprintln!("could not carry over {} with error {}", file, e);
+    }
+
+    println!(
+        "{} task(s) carried over from {} to {}, {} reminder(s) updated, report written to {}",
+        summary.moved.len(),
+        summary.old_year,
+        summary.new_year,
+        summary.reminders,
+        summary.report
+    );
+
+    summary.failed.is_empty()
+}
+
+fn is_same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn read_input(file: &str) -> io::Result<String> {
+    if file == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        return Ok(contents);
+    }
+
+    fs::read_to_string(file)
+}
+
+fn find_task(task_name: &str, new_only: bool, project: &str, exact: bool) -> io::Result<Task> {
+    if let Some(task) = get_task_at_reference(task_name) {
+        return task;
+    }
+
+    if let Ok(task) = Task::get_by_id_or_name(task_name, new_only, project) {
+        return Ok(task);
+    }
+
+    if exact {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "not found"));
+    }
+
+    let mut matches = Task::get_matches(task_name, new_only, project)?;
+    if matches.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "not found"));
+    }
+
+    // A single weak match is still a guess, so it is confirmed like any other
+    let is_clear_match = matches[0].0 >= fuzzy::CLEAR_MATCH
+        && matches.get(1).is_none_or(|m| m.0 < fuzzy::CLEAR_MATCH);
+    if is_clear_match {
+        return Ok(matches.remove(0).1);
+    }
+
+    let candidates: Vec<Task> = matches.into_iter().take(10).map(|(_, t)| t).collect();
+    let is_terminal = io::stdin().is_terminal();
+
+    // Without a terminal to confirm on, a single guess is only suggested
+    if !is_terminal && candidates.len() == 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no exact match, did you mean {} - {}?", candidates[0].project, candidates[0].task_name)
+        ));
+    }
+
+    for (i, task) in candidates.iter().enumerate() {
+        eprintln!("{}) {} - {}", i + 1, task.project, task.task_name);
+    }
+
+    if !is_terminal {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "task name is ambiguous"));
+    }
+
+    eprint!("Select a task [1-{}]: ", candidates.len());
+    io::stderr().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let choice = input.trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|c| *c >= 1 && *c <= candidates.len())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no task selected"))?;
+
+    Ok(candidates[choice - 1].clone())
+}
+
+fn resolve_tasks(
+    task_names: &[String],
+    query: &str,
+    new_only: bool,
+    project: &str,
+    exact: bool
+) -> Vec<(String, io::Result<Task>)> {
+    let mut names: Vec<String> = Vec::new();
+    for name in task_names {
+        if name != "-" {
+            names.push(name.to_string());
+            continue;
+        }
+
+        let input = match read_input("-") {
+            Ok(i) => i,
+            Err(e) => return vec![(name.to_string(), Err(e))],
+        };
+
+        names.extend(
+            input.lines()
+                .map(|l| l.trim())
+                .filter(|l| !l.is_empty())
+                .map(|l| l.to_string())
+        );
+    }
+
+    let mut tasks: Vec<(String, io::Result<Task>)> = names
+        .into_iter()
+        .map(|n| {
+            let task = find_task(&n, new_only, project, exact);
+            (n, task)
+        })
+        .collect();
+
+    if !query.is_empty() {
+        match Task::query(query, new_only, project) {
+            Ok(found) => tasks.extend(
+                found.into_iter()
+                    .filter(|t| !t.is_finished())
+                    .map(|t| (t.task_name.to_string(), Ok(t)))
+            ),
+            Err(e) => tasks.push((query.to_string(), Err(e))),
+        }
     }
+
+    // A task that is named and also matched by the query is only run once
+    let mut seen: HashSet<String> = HashSet::new();
+    tasks.retain(|(_name, task)| match task {
+        Ok(t) => seen.insert(t.id.to_string()),
+        Err(_e) => true,
+    });
+
+    tasks
 }
 
-fn get_project_year(year: &str) -> i32 {
-    let mut project_year = Local::now().year();
-    if year != "" {
-        project_year = year.parse::<i32>().unwrap();
+// Runs an operation on every task, reporting each failure instead of
+// stopping at the first one, and exits non-zero if anything failed
+fn run_batch<F>(action: &str, tasks: Vec<(String, io::Result<Task>)>, op: F)
+where
+    F: Fn(&Task) -> io::Result<()>,
+{
+    if tasks.is_empty() {
+        eprintln!("no tasks matched");
+        process::exit(1);
     }
 
-    project_year
+    if run_each(action, tasks, op) > 0 {
+        process::exit(1);
+    }
+}
+
+// Returns the number of tasks that failed
+fn run_each<F>(action: &str, tasks: Vec<(String, io::Result<Task>)>, op: F) -> usize
+where
+    F: Fn(&Task) -> io::Result<()>,
+{
+    let total = tasks.len();
+    let mut failed = 0;
+    for (name, task) in tasks {
+        match task.and_then(|t| op(&t)) {
+            Ok(()) => {
+                if total > 1 {
+                    println!("{} {}", action, name);
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("failed {}: {}", name, e);
+            }
+        }
+    }
+
+    if total > 1 || failed > 0 {
+        println!("{} succeeded, {} failed", total - failed, failed);
+    }
+
+    failed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use settings::tests::Home;
+
+    fn add_task(task_name: &str, project: &str) -> Task {
+        let task = Task::new(task_name, project, &Local::now().year());
+        task.add("").unwrap();
+        task
+    }
+
+    fn names(tasks: &[(String, io::Result<Task>)]) -> Vec<(String, Option<String>)> {
+        tasks.iter()
+            .map(|(n, t)| (n.to_string(), t.as_ref().ok().map(|t| t.task_name.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_several_tasks() {
+        let home = Home::new();
+        home.init();
+        add_task("Call Bob", "Home");
+        let carol = add_task("Call Carol", "Home");
+        add_task("Write report", "Work");
+        TaskList::get("Today").add(carol).unwrap();
+
+        let task_names = ["Write report".to_string(), "today:1".to_string(), "Nobody".to_string()];
+        let tasks = resolve_tasks(&task_names, "", false, "", true);
+        assert_eq!(names(&tasks), vec![
+            ("Write report".to_string(), Some("Write report".to_string())),
+            ("today:1".to_string(), Some("Call Carol".to_string())),
+            ("Nobody".to_string(), None),
+        ]);
+
+        // Tasks both named and matched by the query only come back once
+        let tasks = resolve_tasks(&["Call Bob".to_string()], "project:Home", false, "", true);
+        let mut found: Vec<String> = tasks.into_iter().map(|(_n, t)| t.unwrap().task_name).collect();
+        found.sort();
+        assert_eq!(found, vec!["Call Bob", "Call Carol"]);
+
+        assert!(resolve_tasks(&[], "project:Garden", false, "", true).is_empty());
+    }
+
+    #[test]
+    fn runs_every_task_and_counts_failures() {
+        let home = Home::new();
+        home.init();
+        add_task("Call Bob", "Home");
+        add_task("Write report", "Work");
+
+        let task_names = ["Call Bob".to_string(), "Nobody".to_string(), "Write report".to_string()];
+        let tasks = resolve_tasks(&task_names, "", false, "", true);
+        let failed = run_each("finished", tasks, |task| match task.project.as_str() {
+            "Work" => Err(io::Error::other("locked")),
+            _ => task.finish("Task Completed"),
+        });
+
+        assert_eq!(failed, 2);
+        assert!(Task::get_by_id_or_name("Call Bob", false, "").unwrap().is_finished());
+        assert!(!Task::get_by_id_or_name("Write report", false, "").unwrap().is_finished());
+        assert_eq!(run_each("finished", Vec::new(), |_task| Ok(())), 0);
+    }
 }
@@ -3,32 +3,144 @@ extern crate chrono;
 
 use std::env;
 use std::fs;
-use std::fs::File;
-use std::io::Result;
-use std::io::Write;
+use std::io::{Error, ErrorKind, Result};
 use std::path::Path;
 use std::path::PathBuf;
 use chrono::prelude::*;
 use chrono::DateTime;
+use toduitl::layout;
+use toduitl::storage;
 
 
+const REQUIRED_SETTINGS: &[&str] = &[
+    "root-folder",
+    "reminder-file",
+    "project-folder-name",
+    "journal-folder-name",
+    "review-folder-name",
+    "todo-lists",
+];
+
 pub struct Settings {
     config: config::Config,
 }
 
 impl Settings {
     pub fn new() -> Settings {
-        let settings_path = get_settings_path().unwrap();
+        let settings_path = get_settings_path();
+        if !settings_path.exists() {
+            eprintln!("no settings found at {}, run `toduit init` to create them", settings_path.display());
+            std::process::exit(1);
+        }
 
-        let mut conf = config::Config::default();
-        conf.merge(config::File::from(settings_path)).unwrap();
+        let new_settings = Settings::load(&settings_path).expect("could not read settings");
+        if let Err(e) = layout::validate(&new_settings.get_path_template()) {
+            eprintln!("{}: {}", settings_path.display(), e);
+            std::process::exit(1);
+        }
+
+        let limit_problems = new_settings.validate_list_limits();
+        for problem in &limit_problems {
+            eprintln!("{}: {}", settings_path.display(), problem);
+        }
+
+        if !limit_problems.is_empty() {
+            std::process::exit(1);
+        }
 
-        let new_settings = Settings { config: conf };
         new_settings.set_environment_variables().expect("could not create env variables");
 
         new_settings
     }
 
+    fn load(settings_path: &Path) -> Result<Settings> {
+        let contents = storage::read_to_string(settings_path)?;
+        let mut conf = config::Config::default();
+        conf.merge(config::File::from_str(&contents, config::FileFormat::Toml))
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+        Ok(Settings { config: conf })
+    }
+
+    // Returns every problem with the settings that would stop commands
+    // from finding or creating tasks
+    fn validate(&self) -> Vec<String> {
+        let mut problems: Vec<String> = REQUIRED_SETTINGS
+            .iter()
+            .filter(|key| self.get_setting(key).trim().is_empty())
+            .map(|key| format!("{} is not set", key))
+            .collect();
+
+        for key in &["root-folder", "reminder-file"] {
+            let value = self.get_setting(key);
+            if !value.is_empty() && !Path::new(&value).is_absolute() {
+                problems.push(format!("{} must be an absolute path, found {}", key, value));
+            }
+        }
+
+        for key in &["project-folder-name", "journal-folder-name", "review-folder-name", "archive-folder-name"] {
+            let value = self.get_setting(key);
+            if value.contains('/') {
+                problems.push(format!("{} must be a single folder name, found {}", key, value));
+            }
+        }
+
+        let todo_lists = self.get_setting("todo-lists");
+        for list in todo_lists.split(',').filter(|_| !todo_lists.is_empty()) {
+            if list.trim().is_empty() || list.contains('/') || list != list.trim() {
+                problems.push(format!("todo-lists has an invalid list name {:?}", list));
+            }
+        }
+
+        if let Err(e) = layout::validate(&self.get_path_template()) {
+            problems.push(e.to_string());
+        }
+
+        problems.append(&mut self.validate_list_limits());
+        problems
+    }
+
+    fn validate_list_limits(&self) -> Vec<String> {
+        let mut problems: Vec<String> = Vec::new();
+        for (key, value) in self.config.get_table("list-limits").unwrap_or_default() {
+            if self.get_list_name(&key).is_none() {
+                problems.push(format!("list-limits has a limit for {}, which is not in todo-lists", key));
+            }
+
+            if !matches!(value.into_int(), Ok(n) if n > 0) {
+                problems.push(format!("list-limits for {} must be a number above 0", key));
+            }
+        }
+
+        problems
+    }
+
+    // Keys are lower case once the settings are read, so they are matched
+    // back to the list names
+    fn get_list_name(&self, key: &str) -> Option<String> {
+        self.get_setting("todo-lists")
+            .split(',')
+            .find(|l| l.eq_ignore_ascii_case(key))
+            .map(|l| l.to_string())
+    }
+
+    fn set_list_limits(&self) {
+        let limits: Vec<String> = self.config.get_table("list-limits")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(key, value)| Some(format!("{}={}", self.get_list_name(&key)?, value.into_int().ok()?)))
+            .collect();
+
+        env::set_var("TODUIT_LIST_LIMITS", limits.join(","));
+    }
+
+    fn get_path_template(&self) -> String {
+        match self.get_setting("path-template") {
+            t if t.is_empty() => layout::DEFAULT_TEMPLATE.to_string(),
+            t => t,
+        }
+    }
+
     fn set_environment_variables(&self) -> Result<()> {
     
         let date = Local::now();
@@ -39,22 +151,26 @@ impl Settings {
         self.set_environment_variable_setting("journal-folder-name", "TODUIT_JOURNAL_FOLDER_NAME");
         self.set_environment_variable_setting("review-folder-name", "TODUIT_REVIEW_FOLDER_NAME");
         self.set_environment_variable_setting("todo-lists", "TODUIT_TODO_LISTS");
+        self.set_environment_variable_setting("api-token", "TODUIT_API_TOKEN");
+        self.set_environment_variable_setting("path-template", "TODUIT_PATH_TEMPLATE");
+        self.set_environment_variable_setting("archive-folder-name", "TODUIT_ARCHIVE_FOLDER_NAME");
+        self.set_list_limits();
         self.set_journal_folder(&date);
         self.set_review_folder(&date);
-        self.set_project_folder(&date);
+        self.set_project_folder();
         
         Ok(())
     }
 
     fn set_environment_variable_setting(&self, key: &str, env_name: &str) {
         let value = self.get_setting(key);
-        if value != "" {
+        if !value.is_empty() {
             env::set_var(env_name, value);
         }
     }
 
     fn get_setting(&self, name: &str) -> String {
-        self.config.get_str(name).unwrap()
+        self.config.get_str(name).unwrap_or_default()
     }
     
     fn set_journal_folder(&self, date: &DateTime<Local>) {
@@ -63,7 +179,7 @@ impl Settings {
         let file_exists = Path::new(&folderpath).exists();
 
         if !file_exists {
-            fs::create_dir_all(&folderpath).expect("could not create path");
+            storage::create_dir_all(&folderpath).expect("could not create path");
         }   
 
         env::set_var("TODUIT_JOURNAL_FOLDER", folderpath);
@@ -75,59 +191,195 @@ impl Settings {
         let file_exists = Path::new(&folderpath).exists();
 
         if !file_exists {
-            fs::create_dir_all(&folderpath).expect("could not create review folder");
+            storage::create_dir_all(&folderpath).expect("could not create review folder");
         }   
 
         env::set_var("TODUIT_REVIEW_FOLDER", folderpath);
     }
 
-    fn set_project_folder(&self, date: &DateTime<Local>) {
-        let folderpath = format!("{}/{}/{}", get_root_folder(), get_project_folder_name(), date.year());
+    // Years are part of the path template, so this is the folder every
+    // task path is relative to
+    fn set_project_folder(&self) {
+        let folderpath = format!("{}/{}", get_root_folder(), get_project_folder_name());
         let file_exists = Path::new(&folderpath).exists();
 
         if !file_exists {
-            fs::create_dir_all(&folderpath).expect("could not create project folder");
+            storage::create_dir_all(&folderpath).expect("could not create project folder");
         }   
 
         env::set_var("TODUIT_PROJECT_FOLDER", folderpath);
     }
 }
 
-fn get_settings_path() -> Result<PathBuf> {
-    let home: PathBuf = match dirs::config_dir() {
-        Some(path) => path.join("todo"),
-        None => PathBuf::from(""),
-    };
+// Writes the settings file when there is none, then creates the folder
+// layout and reminder file for the configured settings. Running it again
+// validates an existing install and fills in anything that is missing
+pub fn init(root: Option<&str>) -> Result<()> {
+    let settings_path = get_settings_path();
+    let root = root.map(|r| {
+        fs::canonicalize(r).unwrap_or_else(|_e| env::current_dir().unwrap_or_default().join(r))
+    });
+
+    if !storage::exists(&settings_path) {
+        let dir = settings_path.parent().unwrap_or_else(|| Path::new(""));
+        storage::create_dir_all(dir)?;
+        create_settings_file(dir, root.as_deref())?;
+        report_created(&format!("settings {}", settings_path.display()));
+    }
+
+    let settings = Settings::load(&settings_path)?;
+    if let Some(r) = &root {
+        let configured = settings.get_setting("root-folder");
+        if Path::new(&configured) != r.as_path() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} already uses root-folder {}, edit it to change the root", settings_path.display(), configured)
+            ));
+        }
+    }
+
+    let problems = settings.validate();
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{}: {}", settings_path.display(), problem);
+        }
+
+        return Err(Error::new(ErrorKind::InvalidData, format!("{} problem(s) found in settings", problems.len())));
+    }
 
-    if !home.exists() {
-        fs::create_dir_all(&home)?;
-        create_settings_file(&home).expect("could not create settings file");
+    settings.set_environment_variables()?;
+
+    let root_folder = get_root_folder();
+    let mut folders: Vec<String> = vec![
+        root_folder.to_string(),
+        format!("{}/{}", root_folder, get_project_folder_name()),
+    ];
+
+    for list in settings.get_setting("todo-lists").split(',') {
+        folders.push(format!("{}/{}", root_folder, list));
+    }
+
+    for folder in folders {
+        if !storage::exists(&folder) {
+            storage::create_dir_all(&folder)?;
+            report_created(&format!("folder {}", folder));
+        }
+    }
+
+    let reminder_file = settings.get_setting("reminder-file");
+    if !storage::exists(&reminder_file) {
+        if let Some(parent) = Path::new(&reminder_file).parent() {
+            storage::create_dir_all(parent)?;
+        }
+
+        storage::write(&reminder_file, "")?;
+        report_created(&format!("reminder file {}", reminder_file));
+    }
+
+    if !toduitl::setting::is_dry_run() {
+        println!("toduit is set up in {}", root_folder);
     }
 
-    Ok(home.join("Settings"))
+    Ok(())
+}
+
+// In a dry run storage prints what would be created instead
+fn report_created(created: &str) {
+    if !toduitl::setting::is_dry_run() {
+        println!("created {}", created);
+    }
 }
 
-fn create_settings_file(dir: &PathBuf) -> Result<()> {
-    let mut settingsfile = File::create(&dir.join("Settings.toml"))?;
+// Changes one setting and keeps the rest of the file as it was. Settings
+// are kept above the list-limits table, or they would be read as limits
+pub fn save_setting(key: &str, value: &str) -> Result<()> {
+    let settings_path = get_settings_path();
+    let contents = storage::read_to_string(&settings_path)?;
+    let new_line = format!("{} = '{}' ", key, value);
+
+    let mut lines: Vec<String> = contents.lines().map(|l| l.to_string()).collect();
+    let table_start = lines.iter().position(|l| l.trim_start().starts_with('[')).unwrap_or(lines.len());
+    let key_line = lines[..table_start].iter().position(|line| match line.split_once('=') {
+        Some((k, _v)) => k.trim() == key,
+        None => false,
+    });
 
-    let home: PathBuf = match dirs::home_dir() {
-        Some(path) => path.join(".local/todo"),
+    match key_line {
+        Some(i) => lines[i] = new_line,
+        None => lines.insert(table_start, new_line),
+    }
+
+    storage::write(&settings_path, &format!("{}\n", lines.join("\n")))
+}
+
+// Writes the list-limits table again at the end of the file, leaving it
+// out when no list has a limit
+pub fn save_list_limits(limits: &[(String, usize)]) -> Result<()> {
+    let settings_path = get_settings_path();
+    let contents = storage::read_to_string(&settings_path)?;
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_limits = false;
+    for line in contents.lines() {
+        if line.trim_start().starts_with('[') {
+            in_limits = line.trim() == "[list-limits]";
+        }
+
+        if !in_limits {
+            lines.push(line.to_string());
+        }
+    }
+
+    while lines.last().map(|l| l.trim().is_empty()).unwrap_or(false) {
+        lines.pop();
+    }
+
+    if !limits.is_empty() {
+        lines.push(String::new());
+        lines.push("[list-limits]".to_string());
+        for (list, limit) in limits {
+            lines.push(format!("{:?} = {}", list, limit));
+        }
+    }
+
+    let limits: Vec<String> = limits.iter().map(|(l, n)| format!("{}={}", l, n)).collect();
+    env::set_var("TODUIT_LIST_LIMITS", limits.join(","));
+
+    storage::write(&settings_path, &format!("{}\n", lines.join("\n")))
+}
+
+fn get_settings_path() -> PathBuf {
+    let home: PathBuf = match dirs::config_dir() {
+        Some(path) => path.join("todo"),
         None => PathBuf::from(""),
     };
 
+    home.join("Settings.toml")
+}
+
+fn create_settings_file(dir: &Path, root: Option<&Path>) -> Result<()> {
+    let home: PathBuf = match (root, dirs::home_dir()) {
+        (Some(r), _) => r.to_path_buf(),
+        (None, Some(path)) => path.join(".local/todo"),
+        (None, None) => PathBuf::from(""),
+    };
+
     let reminder_path: PathBuf = match dirs::home_dir() {
         Some(path) => path.join(".reminders"),
         None => PathBuf::from(""),
     };
 
-    settingsfile.write_all(format!("root-folder = {:?} \n", home).as_bytes())?;
-    settingsfile.write_all(format!("reminder-file = {:?} \n", reminder_path).as_bytes())?;
-    settingsfile.write_all(b"project-folder-name = 'Projects' \n")?;
-    settingsfile.write_all(b"journal-folder-name = 'Journal' \n")?;
-    settingsfile.write_all(b"review-folder-name = 'Review' \n")?;
-    settingsfile.write_all(b"todo-lists = 'Queued,Today,Waiting' \n")?;
+    let contents = [
+        format!("root-folder = {:?} \n", home),
+        format!("reminder-file = {:?} \n", reminder_path),
+        "project-folder-name = 'Projects' \n".to_string(),
+        "journal-folder-name = 'Journal' \n".to_string(),
+        "review-folder-name = 'Review' \n".to_string(),
+        "todo-lists = 'Queued,Today,Waiting' \n".to_string(),
+        format!("path-template = '{}' \n", layout::DEFAULT_TEMPLATE),
+    ].concat();
 
-    Ok(())
+    storage::write(dir.join("Settings.toml"), &contents)
 }
 
 fn get_root_folder() -> String {
@@ -145,3 +397,139 @@ fn get_journal_folder_name() -> String {
 fn get_review_folder_name() -> String {
     env::var("TODUIT_REVIEW_FOLDER_NAME").expect("review folder variable not set")
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Mutex, MutexGuard};
+
+    static LOCK: Mutex<()> = Mutex::new(());
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    // Settings live in the environment, so tests that use them run one at
+    // a time, each with its own home folder that is removed after
+    pub struct Home {
+        pub path: String,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl Home {
+        pub fn new() -> Home {
+            let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let path = env::temp_dir()
+                .join(format!("toduit-home-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::SeqCst)))
+                .to_str()
+                .unwrap()
+                .to_string();
+
+            fs::create_dir_all(&path).unwrap();
+            for var in &["TODUIT_DRY_RUN", "TODUIT_LIST_LIMITS", "TODUIT_YEAR"] {
+                env::remove_var(var);
+            }
+
+            env::set_var("HOME", &path);
+            env::set_var("XDG_CONFIG_HOME", format!("{}/.config", path));
+
+            Home { path, _lock: lock }
+        }
+
+        // Sets up a root the way `toduit init` does and loads its settings
+        pub fn init(&self) -> String {
+            let root = format!("{}/todo", self.path);
+            init(Some(&root)).unwrap();
+            Settings::new();
+            root
+        }
+
+        pub fn get_files(&self) -> Vec<String> {
+            let mut files: Vec<String> = walkdir::WalkDir::new(&self.path)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.path().to_str().map(|p| p.to_string()))
+                .filter(|p| *p != self.path)
+                .collect();
+
+            files.sort();
+            files
+        }
+    }
+
+    impl Drop for Home {
+        fn drop(&mut self) {
+            env::remove_var("TODUIT_DRY_RUN");
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+
+    #[test]
+    fn init_creates_layout() {
+        let home = Home::new();
+        let root = home.init();
+
+        for folder in &["Projects", "Queued", "Today", "Waiting"] {
+            assert!(Path::new(&format!("{}/{}", root, folder)).is_dir(), "{}", folder);
+        }
+
+        assert!(Path::new(&format!("{}/.reminders", home.path)).is_file());
+        let contents = fs::read_to_string(get_settings_path()).unwrap();
+        assert!(contents.contains(&format!("root-folder = {:?}", root)));
+
+        // Running it again only validates
+        init(Some(&root)).unwrap();
+        assert!(init(Some(&format!("{}/other", home.path))).is_err());
+    }
+
+    #[test]
+    fn saves_settings_above_tables() {
+        let home = Home::new();
+        home.init();
+        save_list_limits(&[("Today".to_string(), 5)]).unwrap();
+
+        save_setting("todo-lists", "Today,Queued,Waiting,Someday,Later").unwrap();
+        save_setting("path-template", "{project}/{status}/{name}.md").unwrap();
+
+        let contents = fs::read_to_string(get_settings_path()).unwrap();
+        let table = contents.find("[list-limits]").unwrap();
+        assert_eq!(contents.matches("todo-lists = ").count(), 1);
+        assert!(contents.find("todo-lists = 'Today,Queued,Waiting,Someday,Later'").unwrap() < table);
+        assert!(contents.find("path-template = '{project}/{status}/{name}.md'").unwrap() < table);
+
+        let settings = Settings::load(&get_settings_path()).unwrap();
+        assert_eq!(settings.get_setting("todo-lists"), "Today,Queued,Waiting,Someday,Later");
+        assert_eq!(settings.get_list_name("later"), Some("Later".to_string()));
+    }
+
+    #[test]
+    fn saves_list_limits() {
+        let home = Home::new();
+        home.init();
+
+        save_list_limits(&[("Today".to_string(), 5), ("Waiting".to_string(), 10)]).unwrap();
+        save_list_limits(&[("Today".to_string(), 3), ("Waiting".to_string(), 10)]).unwrap();
+        let contents = fs::read_to_string(get_settings_path()).unwrap();
+        assert_eq!(contents.matches("[list-limits]").count(), 1);
+        assert!(contents.ends_with("\n\n[list-limits]\n\"Today\" = 3\n\"Waiting\" = 10\n"), "{}", contents);
+
+        env::remove_var("TODUIT_LIST_LIMITS");
+        Settings::new();
+        let mut limits = toduitl::setting::get_list_limits();
+        limits.sort();
+        assert_eq!(limits, vec![("Today".to_string(), 3), ("Waiting".to_string(), 10)]);
+
+        save_list_limits(&[]).unwrap();
+        let contents = fs::read_to_string(get_settings_path()).unwrap();
+        assert!(!contents.contains("list-limits"));
+        assert!(toduitl::setting::get_list_limits().is_empty());
+    }
+
+    #[test]
+    fn dry_run_init_writes_nothing() {
+        let home = Home::new();
+        env::set_var("TODUIT_DRY_RUN", "1");
+
+        init(Some(&format!("{}/todo", home.path))).unwrap();
+
+        assert!(home.get_files().is_empty(), "{:?}", home.get_files());
+    }
+}